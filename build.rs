@@ -0,0 +1,4 @@
+// embedded migrations have to be recompiled when they change
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,64 @@
+use athlete_tracker::models::error::Problem;
+use reqwest::StatusCode;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter};
+
+/// Failure of a call to the tracker
+#[derive(Debug)]
+pub enum Error {
+    /// Problem details reported by the service, see [`Problem::code`]
+    Api(Box<Problem>),
+    /// Error status without problem details, e.g. from a proxy in front of the service
+    Status {
+        status: StatusCode,
+        body: String,
+    },
+    /// Request couldn't be sent or the response couldn't be read
+    Http(reqwest::Error),
+    InvalidUrl(String),
+}
+
+impl Error {
+    /// Stable code of the problem reported by the service, e.g. `sportsman_not_found`
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Error::Api(problem) => Some(&problem.code),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::Api(problem) => StatusCode::from_u16(problem.status).ok(),
+            Error::Status { status, .. } => Some(*status),
+            Error::Http(e) => e.status(),
+            Error::InvalidUrl(_) => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Api(problem) => write!(f, "{}: {}", problem.code, problem.detail),
+            Error::Status { status, body } => write!(f, "Unexpected response {status}: {body}"),
+            Error::Http(e) => write!(f, "Request failed: {e}"),
+            Error::InvalidUrl(reason) => write!(f, "Invalid url: {reason}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
@@ -0,0 +1,282 @@
+mod error;
+mod sport;
+
+pub use athlete_tracker::models::audit::Operation;
+pub use athlete_tracker::models::error::Problem;
+pub use athlete_tracker::models::metrics;
+pub use athlete_tracker::models::responses::Page;
+pub use athlete_tracker::models::sportsman::{Profile, Sex};
+pub use athlete_tracker::models::units::UnitSystem;
+pub use athlete_tracker::models::validation::FieldError;
+pub use athlete_tracker::service::models;
+pub use error::Error;
+pub use sport::Sport;
+
+use athlete_tracker::service::auth::API_KEY_HEADER;
+use models::{
+    AuditQuery, AuditRecord, ImportReport, LatestPerformance, LeaderboardQuery, ListQuery,
+    Liveness, Message, NewSportsman, Ranking, Readiness, Recorded, SportsmanOverview,
+    SportsmanProfile, SportsmanUpdate, UnitsQuery,
+};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Client of the tracker API. Typed methods are generic over the built-in sports,
+/// the `*_of` ones take the sport's name and work with sports defined at runtime too
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    units: UnitSystem,
+    credentials: Option<Credentials>,
+}
+
+#[derive(Debug, Clone)]
+enum Credentials {
+    ApiKey(String),
+    Token(String),
+}
+
+impl Client {
+    /// `base_url` is the address the service is available at, e.g. `http://localhost:8080`
+    pub fn new(base_url: &str) -> Result<Self, Error> {
+        Self::with_http(reqwest::Client::new(), base_url)
+    }
+
+    /// Uses the configured http client, e.g. with timeouts or default headers
+    pub fn with_http(http: reqwest::Client, base_url: &str) -> Result<Self, Error> {
+        let base_url = Url::parse(base_url).map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        if base_url.cannot_be_a_base() {
+            return Err(Error::InvalidUrl(format!("{base_url} can't be a base")));
+        }
+
+        Ok(Self {
+            http,
+            base_url,
+            units: UnitSystem::default(),
+            credentials: None,
+        })
+    }
+
+    /// Units the performances are returned in, metric by default
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Key sent in `X-API-Key` header
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.credentials = Some(Credentials::ApiKey(key.into()));
+        self
+    }
+
+    /// JWT sent as bearer token
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.credentials = Some(Credentials::Token(token.into()));
+        self
+    }
+
+    pub async fn live(&self) -> Result<Liveness, Error> {
+        self.send(self.http.get(self.url(&["health", "live"])))
+            .await
+    }
+
+    /// Not ready service answers with the failed checks rather than with problem details
+    pub async fn ready(&self) -> Result<Readiness, Error> {
+        let response = self.http.get(self.url(&["health", "ready"])).send().await?;
+        match response.status() {
+            StatusCode::OK | StatusCode::SERVICE_UNAVAILABLE => Ok(response.json().await?),
+            _ => Err(Self::error(response).await),
+        }
+    }
+
+    pub async fn sportsmen(&self, query: &ListQuery) -> Result<Page<SportsmanOverview>, Error> {
+        self.send(self.http.get(self.url(&["sportsmen"])).query(query))
+            .await
+    }
+
+    pub async fn sportsman(&self, name: &str) -> Result<SportsmanProfile, Error> {
+        self.send(self.http.get(self.url(&["sportsmen", name])))
+            .await
+    }
+
+    pub async fn create_sportsman(&self, sportsman: &NewSportsman) -> Result<Message, Error> {
+        self.send(self.http.post(self.url(&["sportsmen"])).json(sportsman))
+            .await
+    }
+
+    pub async fn update_sportsman(
+        &self,
+        name: &str,
+        update: &SportsmanUpdate,
+    ) -> Result<Message, Error> {
+        self.send(self.http.patch(self.url(&["sportsmen", name])).json(update))
+            .await
+    }
+
+    /// Removes the sportsman with all the performances
+    pub async fn remove_sportsman(&self, name: &str) -> Result<Message, Error> {
+        self.send(self.http.delete(self.url(&["sportsmen", name])))
+            .await
+    }
+
+    pub async fn add_performance<P>(&self, name: &str, performance: &P) -> Result<Message, Error>
+    where
+        P: Sport + Serialize,
+    {
+        self.add_performance_of(P::NAME, name, performance).await
+    }
+
+    /// Latest performance of the sportsman
+    pub async fn performance<T>(&self, name: &str) -> Result<T, Error>
+    where
+        T: Sport + DeserializeOwned,
+    {
+        self.performance_of(T::NAME, name).await
+    }
+
+    /// All performances from the oldest to the latest
+    pub async fn history<T>(&self, name: &str) -> Result<Vec<Recorded<T>>, Error>
+    where
+        T: Sport + DeserializeOwned,
+    {
+        self.history_of(T::NAME, name).await
+    }
+
+    /// Latest performances of all sportsmen
+    pub async fn performances<T>(
+        &self,
+        query: &ListQuery,
+    ) -> Result<Page<LatestPerformance<T>>, Error>
+    where
+        T: Sport + DeserializeOwned,
+    {
+        self.performances_of(T::NAME, query).await
+    }
+
+    pub async fn add_performance_of<P: Serialize + ?Sized>(
+        &self,
+        sport: &str,
+        name: &str,
+        performance: &P,
+    ) -> Result<Message, Error> {
+        self.send(self.http.post(self.url(&[sport, name])).json(performance))
+            .await
+    }
+
+    pub async fn performance_of<T: DeserializeOwned>(
+        &self,
+        sport: &str,
+        name: &str,
+    ) -> Result<T, Error> {
+        self.send(self.in_units(self.http.get(self.url(&[sport, name]))))
+            .await
+    }
+
+    pub async fn history_of<T: DeserializeOwned>(
+        &self,
+        sport: &str,
+        name: &str,
+    ) -> Result<Vec<Recorded<T>>, Error> {
+        let url = self.url(&[sport, name, "history"]);
+        self.send(self.in_units(self.http.get(url))).await
+    }
+
+    pub async fn performances_of<T: DeserializeOwned>(
+        &self,
+        sport: &str,
+        query: &ListQuery,
+    ) -> Result<Page<LatestPerformance<T>>, Error> {
+        let request = self.http.get(self.url(&[sport])).query(query);
+        self.send(self.in_units(request)).await
+    }
+
+    pub async fn leaderboard(
+        &self,
+        sport: &str,
+        query: &LeaderboardQuery,
+    ) -> Result<Ranking, Error> {
+        let request = self
+            .http
+            .get(self.url(&[sport, "leaderboard"]))
+            .query(query);
+        self.send(self.in_units(request)).await
+    }
+
+    /// Stores valid rows of the CSV file with `name` column and columns of the sport's fields,
+    /// the report lists the rejected rows
+    pub async fn import_csv(
+        &self,
+        sport: &str,
+        csv: impl Into<String>,
+    ) -> Result<ImportReport, Error> {
+        let request = self
+            .http
+            .post(self.url(&["import", sport]))
+            .header(CONTENT_TYPE, "text/csv")
+            .body(csv.into());
+        self.send(request).await
+    }
+
+    /// Removes the whole history of the sport
+    pub async fn remove_performance(&self, sport: &str, name: &str) -> Result<Message, Error> {
+        self.send(self.http.delete(self.url(&[sport, name]))).await
+    }
+
+    /// Changes of performances of the caller's tenant from the oldest
+    pub async fn audit(&self, query: &AuditQuery) -> Result<Page<AuditRecord>, Error> {
+        self.send(self.http.get(self.url(&["audit"])).query(query))
+            .await
+    }
+
+    /// Segments are percent-encoded, so names may contain spaces and slashes
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("base url is checked in the constructor")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    fn in_units(&self, request: RequestBuilder) -> RequestBuilder {
+        request.query(&UnitsQuery {
+            units: Some(self.units),
+        })
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        let request = match &self.credentials {
+            Some(Credentials::ApiKey(key)) => request.header(API_KEY_HEADER, key),
+            Some(Credentials::Token(token)) => request.bearer_auth(token),
+            None => request,
+        };
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(Self::error(response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Problem details are decoded if the service sent them
+    async fn error(response: reqwest::Response) -> Error {
+        let status = response.status();
+        let is_problem = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/problem+json"));
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return Error::Http(e),
+        };
+
+        match serde_json::from_str(&body) {
+            Ok(problem) if is_problem => Error::Api(Box::new(problem)),
+            _ => Error::Status { status, body },
+        }
+    }
+}
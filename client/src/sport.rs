@@ -0,0 +1,56 @@
+use athlete_tracker::models::metrics::biathlon::Biathlon;
+use athlete_tracker::models::metrics::cycling::Cycling;
+use athlete_tracker::models::metrics::running::Running;
+use athlete_tracker::models::metrics::swimming::Swimming;
+use athlete_tracker::models::metrics::weight_lifting::WeightLifting;
+use athlete_tracker::service::models::{
+    BiathlonPerformance, CyclingPerformance, RunningPerformance, SwimmingPerformance,
+    WeightLiftingPerformance,
+};
+
+/// Built-in sport the request model or the record belongs to,
+/// sports defined at runtime are used through the `*_of` methods of the client
+pub trait Sport {
+    /// Name used in routes, e.g. `running`
+    const NAME: &'static str;
+}
+
+impl Sport for Running {
+    const NAME: &'static str = "running";
+}
+
+impl Sport for RunningPerformance {
+    const NAME: &'static str = "running";
+}
+
+impl Sport for Biathlon {
+    const NAME: &'static str = "biathlon";
+}
+
+impl Sport for BiathlonPerformance {
+    const NAME: &'static str = "biathlon";
+}
+
+impl Sport for WeightLifting {
+    const NAME: &'static str = "weight_lifting";
+}
+
+impl Sport for WeightLiftingPerformance {
+    const NAME: &'static str = "weight_lifting";
+}
+
+impl Sport for Swimming {
+    const NAME: &'static str = "swimming";
+}
+
+impl Sport for SwimmingPerformance {
+    const NAME: &'static str = "swimming";
+}
+
+impl Sport for Cycling {
+    const NAME: &'static str = "cycling";
+}
+
+impl Sport for CyclingPerformance {
+    const NAME: &'static str = "cycling";
+}
@@ -0,0 +1,645 @@
+use athlete_tracker::service::auth::Auth;
+use athlete_tracker::service::config::{AuthConfig, Config, PoolConfig, RetryPolicy};
+use athlete_tracker::service::core::{Service, Storage, Url};
+use athlete_tracker::service::memory::memory_pool::MemoryPool;
+use athlete_tracker::service::registry::SportRegistry;
+use athlete_tracker_client::metrics::running::Running;
+use athlete_tracker_client::metrics::swimming::{Stroke, Swimming};
+use athlete_tracker_client::models::{
+    AuditQuery, LeaderboardQuery, ListQuery, NewSportsman, Order, RunningPerformance,
+    SportsmanUpdate, SwimmingPerformance,
+};
+use athlete_tracker_client::{Client, Error, Operation, Profile, UnitSystem};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const JWT_SECRET: &str = "secret-of-the-tokens-used-in-tests";
+
+/// Serves the API on a free port with the memory storage, the sports from the example schema
+/// are registered too and the keys from the example keys file are accepted. Returns the base url
+async fn start_service() -> String {
+    let config = Config {
+        storage: Storage::Memory,
+        service_url: Url("127.0.0.1:0".to_string()),
+        shutdown_timeout: Duration::from_secs(1),
+        sports_schema: None,
+        auth: AuthConfig {
+            keys_file: Some(example("api_keys.example.toml")),
+            jwt_secret: Some(JWT_SECRET.to_string()),
+            disabled: false,
+        },
+        pool: PoolConfig {
+            max_connections: 1,
+            connect_timeout: Duration::from_secs(1),
+            retry: RetryPolicy {
+                attempts: 1,
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(10),
+            },
+        },
+    };
+    let registry = SportRegistry::load(Some(&example("sports.example.toml")))
+        .expect("example schema is valid");
+
+    let auth = Auth::load(&config.auth).expect("example keys are valid");
+
+    let service = Service::new(MemoryPool::new(), registry, auth, &config)
+        .await
+        .expect("service starts");
+    let addr = service.local_addr().expect("service is bound");
+    tokio::spawn(service.start());
+
+    format!("http://{addr}")
+}
+
+fn example(file: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join(file)
+}
+
+/// Admin client
+async fn client() -> Client {
+    Client::new(&start_service().await)
+        .expect("valid url")
+        .with_api_key("admin-dev-key")
+}
+
+/// HS256 token signed with the secret of the service
+fn token(claims: Value) -> String {
+    let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "HS256", "typ": "JWT" }).to_string());
+    let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let mut mac = Hmac::<Sha256>::new_from_slice(JWT_SECRET.as_bytes()).unwrap();
+    mac.update(format!("{header}.{payload}").as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+    format!("{header}.{payload}.{signature}")
+}
+
+fn expires_in(secs: i64) -> i64 {
+    chrono::Utc::now().timestamp() + secs
+}
+
+fn running(distance: f32, speed: f32) -> RunningPerformance {
+    RunningPerformance { distance, speed }
+}
+
+#[tokio::test]
+async fn manages_sportsmen() {
+    let client = client().await;
+
+    let created = client
+        .create_sportsman(&NewSportsman {
+            name: "John".to_string(),
+            profile: Profile {
+                body_mass: Some(72.5),
+                nationality: Some("NOR".to_string()),
+                ..Profile::default()
+            },
+        })
+        .await
+        .unwrap();
+    assert_eq!(created.message, "Sportsman created successfully");
+
+    let profile = client.sportsman("John").await.unwrap();
+    assert_eq!(profile.body_mass, Some(72.5));
+    assert_eq!(profile.nationality.as_deref(), Some("NOR"));
+
+    client
+        .update_sportsman(
+            "John",
+            &SportsmanUpdate {
+                name: Some("John Smith".to_string()),
+                body_mass: Some(None),
+                ..SportsmanUpdate::default()
+            },
+        )
+        .await
+        .unwrap();
+    let profile = client.sportsman("John Smith").await.unwrap();
+    assert_eq!(profile.body_mass, None);
+    assert_eq!(profile.nationality.as_deref(), Some("NOR"));
+
+    let page = client.sportsmen(&ListQuery::default()).await.unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].profile.name, "John Smith");
+
+    client.remove_sportsman("John Smith").await.unwrap();
+    let error = client.sportsman("John Smith").await.unwrap_err();
+    assert_eq!(error.code(), Some("sportsman_not_found"));
+    assert_eq!(error.status(), Some(StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn adds_and_reads_typed_performances() {
+    let client = client().await;
+
+    let added = client
+        .add_performance("Ann", &running(10.0, 12.5))
+        .await
+        .unwrap();
+    assert_eq!(added.message, "Running performance added successfully");
+    client
+        .add_performance("Ann", &running(21.1, 11.0))
+        .await
+        .unwrap();
+    client
+        .add_performance(
+            "Ann",
+            &SwimmingPerformance {
+                distance: 400.0,
+                time: 262.5,
+                stroke: Stroke::Freestyle,
+                pool_length: 50.0,
+            },
+        )
+        .await
+        .unwrap();
+
+    let latest: Running = client.performance("Ann").await.unwrap();
+    assert_eq!(latest.distance.0, 21.1);
+
+    let swimming: Swimming = client.performance("Ann").await.unwrap();
+    assert_eq!(swimming.stroke, Stroke::Freestyle);
+
+    let history = client.history::<Running>("Ann").await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].performance.speed.0, 12.5);
+    assert!(history[0].recorded_at <= history[1].recorded_at);
+
+    let page = client
+        .performances::<Running>(&ListQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+    assert_eq!(page.items[0].name, "Ann");
+    assert_eq!(page.items[0].performance.performance.distance.0, 21.1);
+
+    client.remove_performance("running", "Ann").await.unwrap();
+    let error = client.performance::<Running>("Ann").await.unwrap_err();
+    assert_eq!(error.code(), Some("performance_not_found"));
+}
+
+#[tokio::test]
+async fn converts_units() {
+    let client = client().await;
+    client
+        .add_performance("Bob", &running(16.09344, 16.09344))
+        .await
+        .unwrap();
+
+    let imperial = client.clone().with_units(UnitSystem::Imperial);
+    let latest: Running = imperial.performance("Bob").await.unwrap();
+    assert!((latest.distance.0 - 10.0).abs() < 1e-4);
+
+    let ranking = imperial
+        .leaderboard(
+            "running",
+            &LeaderboardQuery {
+                by: "speed".to_string(),
+                order: Some(Order::Desc),
+                limit: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(ranking.unit.as_deref(), Some("mph"));
+    assert_eq!(ranking.entries[0].name, "Bob");
+    assert!((ranking.entries[0].value - 10.0).abs() < 1e-4);
+}
+
+#[tokio::test]
+async fn decodes_problems() {
+    let client = client().await;
+
+    let error = client
+        .add_performance("Ann", &running(-1.0, 0.0))
+        .await
+        .unwrap_err();
+    let Error::Api(problem) = &error else {
+        panic!("expected problem details, got {error:?}");
+    };
+    assert_eq!(problem.status, 422);
+    assert_eq!(problem.code, "invalid_performance");
+    let fields: Vec<&str> = problem.errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(fields, ["distance", "speed"]);
+    assert!(problem.request_id.is_some());
+
+    let error = client
+        .leaderboard(
+            "running",
+            &LeaderboardQuery {
+                by: "stroke".to_string(),
+                ..LeaderboardQuery::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    let Error::Api(problem) = &error else {
+        panic!("expected problem details, got {error:?}");
+    };
+    assert_eq!(problem.code, "invalid_ranking_field");
+    assert_eq!(problem.expected, ["distance", "speed"]);
+
+    let error = client
+        .performance_of::<Value>("tennis", "Ann")
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("sport_not_found"));
+
+    let error = client
+        .remove_performance("running", "leaderboard")
+        .await
+        .unwrap_err();
+    let Error::Api(problem) = &error else {
+        panic!("expected problem details, got {error:?}");
+    };
+    assert_eq!(problem.status, 405);
+    assert_eq!(problem.code, "method_not_allowed");
+
+    let new = NewSportsman {
+        name: "Ann".to_string(),
+        profile: Profile::default(),
+    };
+    client.create_sportsman(&new).await.unwrap();
+    let error = client.create_sportsman(&new).await.unwrap_err();
+    assert_eq!(error.code(), Some("sportsman_already_exists"));
+    assert_eq!(error.status(), Some(StatusCode::CONFLICT));
+
+    let blank = NewSportsman {
+        name: " ".to_string(),
+        profile: Profile::default(),
+    };
+    let error = client.create_sportsman(&blank).await.unwrap_err();
+    assert_eq!(error.code(), Some("invalid_name"));
+
+    let reserved = NewSportsman {
+        name: "leaderboard".to_string(),
+        profile: Profile::default(),
+    };
+    let error = client.create_sportsman(&reserved).await.unwrap_err();
+    assert_eq!(error.code(), Some("invalid_name"));
+}
+
+#[tokio::test]
+async fn reports_unknown_routes_as_problems() {
+    let client = Client::new(&format!("{}/unknown", start_service().await))
+        .unwrap()
+        .with_api_key("admin-dev-key");
+
+    let error = client.live().await.unwrap_err();
+    let Error::Api(problem) = &error else {
+        panic!("expected problem details, got {error:?}");
+    };
+    assert_eq!(problem.status, StatusCode::NOT_FOUND.as_u16());
+    assert_eq!(problem.code, "route_not_found");
+    assert_eq!(problem.instance.as_deref(), Some("/unknown/health/live"));
+}
+
+#[tokio::test]
+async fn works_with_sports_defined_at_runtime() {
+    let client = client().await;
+
+    client
+        .add_performance_of(
+            "rowing",
+            "Eve",
+            &json!({ "distance": 2000, "time": 420.5, "stroke_rate": 30, "boat": "single" }),
+        )
+        .await
+        .unwrap();
+
+    let latest: Value = client.performance_of("rowing", "Eve").await.unwrap();
+    assert_eq!(latest["boat"], "single");
+    assert_eq!(latest["stroke_rate"], 30);
+
+    let history = client.history_of::<Value>("rowing", "Eve").await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].performance["distance"], 2000.0);
+
+    let error = client
+        .add_performance_of("rowing", "Eve", &json!({ "distance": 2000 }))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("invalid_performance"));
+}
+
+#[tokio::test]
+async fn checks_health() {
+    let client = client().await;
+
+    assert_eq!(client.live().await.unwrap().status, "ok");
+    let ready = client.ready().await.unwrap();
+    assert_eq!(ready.status, "ready");
+    assert_eq!(ready.checks.database, "ok");
+    assert_eq!(ready.checks.tracker, "ok");
+}
+
+#[tokio::test]
+async fn encodes_names_in_paths() {
+    let client = client().await;
+
+    client
+        .add_performance("Anna Maria/Jr", &running(5.0, 10.0))
+        .await
+        .unwrap();
+    let latest: Running = client.performance("Anna Maria/Jr").await.unwrap();
+    assert_eq!(latest.distance.0, 5.0);
+}
+
+#[tokio::test]
+async fn requires_credentials() {
+    let url = start_service().await;
+
+    let anonymous = Client::new(&url).unwrap();
+    let error = anonymous.sportsman("Ann").await.unwrap_err();
+    assert_eq!(error.code(), Some("unauthenticated"));
+    assert_eq!(error.status(), Some(StatusCode::UNAUTHORIZED));
+    assert_eq!(anonymous.live().await.unwrap().status, "ok");
+
+    let error = anonymous
+        .with_api_key("unknown-key")
+        .sportsman("Ann")
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("unauthenticated"));
+
+    let expired = token(json!({ "sub": "coach", "role": "coach", "exp": expires_in(-60) }));
+    let error = Client::new(&url)
+        .unwrap()
+        .with_token(expired)
+        .sportsmen(&ListQuery::default())
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("unauthenticated"));
+
+    let mut forged = token(json!({ "sub": "ann", "role": "admin", "exp": expires_in(60) }));
+    forged.pop();
+    let error = Client::new(&url)
+        .unwrap()
+        .with_token(forged)
+        .sportsmen(&ListQuery::default())
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("unauthenticated"));
+}
+
+#[tokio::test]
+async fn limits_athletes_to_own_data() {
+    let url = start_service().await;
+    let admin = Client::new(&url).unwrap().with_api_key("admin-dev-key");
+    admin
+        .add_performance("Ann", &running(10.0, 12.5))
+        .await
+        .unwrap();
+    admin
+        .add_performance("Bob", &running(5.0, 10.0))
+        .await
+        .unwrap();
+
+    let ann = Client::new(&url).unwrap().with_api_key("athlete-dev-key");
+    let latest: Running = ann.performance("Ann").await.unwrap();
+    assert_eq!(latest.distance.0, 10.0);
+
+    let error = ann.performance::<Running>("Bob").await.unwrap_err();
+    assert_eq!(error.code(), Some("forbidden"));
+    assert_eq!(error.status(), Some(StatusCode::FORBIDDEN));
+
+    let error = ann
+        .performances::<Running>(&ListQuery::default())
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("forbidden"));
+
+    let error = ann
+        .add_performance("Ann", &running(42.2, 12.0))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("forbidden"));
+}
+
+#[tokio::test]
+async fn lets_coaches_change_data_of_their_squad() {
+    let url = start_service().await;
+    let coach = Client::new(&url).unwrap().with_token(token(json!({
+        "sub": "coach",
+        "role": "coach",
+        "squad": ["Ann"],
+        "exp": expires_in(60),
+    })));
+
+    coach
+        .add_performance("Ann", &running(10.0, 12.5))
+        .await
+        .unwrap();
+    let error = coach
+        .add_performance("Bob", &running(10.0, 12.5))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("forbidden"));
+
+    let page = coach
+        .performances::<Running>(&ListQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(page.total, 1);
+
+    let renamed = SportsmanUpdate {
+        name: Some("Bob".to_string()),
+        ..SportsmanUpdate::default()
+    };
+    let error = coach.update_sportsman("Ann", &renamed).await.unwrap_err();
+    assert_eq!(error.code(), Some("forbidden"));
+
+    let error = coach.remove_sportsman("Ann").await.unwrap_err();
+    assert_eq!(error.code(), Some("forbidden"));
+}
+
+#[tokio::test]
+async fn isolates_tenants() {
+    let url = start_service().await;
+    let default = Client::new(&url).unwrap().with_api_key("admin-dev-key");
+    let bergen = Client::new(&url).unwrap().with_token(token(json!({
+        "sub": "bergen-admin",
+        "role": "admin",
+        "tenant": "bergen",
+        "exp": expires_in(60),
+    })));
+
+    default
+        .add_performance("Ann", &running(10.0, 12.5))
+        .await
+        .unwrap();
+    bergen
+        .add_performance("Ann", &running(5.0, 10.0))
+        .await
+        .unwrap();
+    bergen
+        .add_performance("Eve", &running(3.0, 9.0))
+        .await
+        .unwrap();
+
+    let latest: Running = default.performance("Ann").await.unwrap();
+    assert_eq!(latest.distance.0, 10.0);
+    let latest: Running = bergen.performance("Ann").await.unwrap();
+    assert_eq!(latest.distance.0, 5.0);
+
+    let error = default.performance::<Running>("Eve").await.unwrap_err();
+    assert_eq!(error.code(), Some("sportsman_not_found"));
+    assert_eq!(
+        default
+            .sportsmen(&ListQuery::default())
+            .await
+            .unwrap()
+            .total,
+        1
+    );
+
+    bergen.remove_sportsman("Ann").await.unwrap();
+    let latest: Running = default.performance("Ann").await.unwrap();
+    assert_eq!(latest.distance.0, 10.0);
+}
+
+#[tokio::test]
+async fn audits_changes_of_performances() {
+    let url = start_service().await;
+    let admin = Client::new(&url).unwrap().with_api_key("admin-dev-key");
+    let coach = Client::new(&url).unwrap().with_api_key("coach-dev-key");
+    let athlete = Client::new(&url).unwrap().with_api_key("athlete-dev-key");
+
+    coach
+        .add_performance("Ann", &running(10.0, 12.5))
+        .await
+        .unwrap();
+    coach
+        .add_performance("Ann", &running(12.0, 13.0))
+        .await
+        .unwrap();
+    coach
+        .add_performance("Bob", &running(5.0, 10.0))
+        .await
+        .unwrap();
+    admin.remove_performance("running", "Ann").await.unwrap();
+    let renamed = SportsmanUpdate {
+        name: Some("Robert".to_string()),
+        ..SportsmanUpdate::default()
+    };
+    admin.update_sportsman("Bob", &renamed).await.unwrap();
+    admin.remove_sportsman("Robert").await.unwrap();
+
+    let log = admin.audit(&AuditQuery::default()).await.unwrap();
+    assert_eq!(log.total, 6);
+    let operations: Vec<(Operation, &str, &str)> = log
+        .items
+        .iter()
+        .map(|e| (e.operation, e.actor.as_str(), e.sportsman.as_str()))
+        .collect();
+    assert_eq!(
+        operations,
+        [
+            (Operation::Add, "coach", "Ann"),
+            (Operation::Add, "coach", "Ann"),
+            (Operation::Add, "coach", "Bob"),
+            (Operation::Remove, "admin", "Ann"),
+            (Operation::Rename, "admin", "Robert"),
+            (Operation::RemoveSportsman, "admin", "Robert"),
+        ]
+    );
+
+    let replaced = &log.items[1];
+    assert_eq!(replaced.before.as_ref().unwrap()["distance"], 10.0);
+    assert_eq!(replaced.after.as_ref().unwrap()["distance"], 12.0);
+    let removed = log.items[3].before.as_ref().unwrap().as_array().unwrap();
+    assert_eq!(removed.len(), 2);
+    assert_eq!(log.items[4].before.as_ref().unwrap()["name"], "Bob");
+    assert_eq!(log.items[4].after.as_ref().unwrap()["name"], "Robert");
+    assert_eq!(log.items[5].before.as_ref().unwrap()[0]["speed"], 10.0);
+
+    let own = athlete
+        .audit(&AuditQuery {
+            sportsman: Some("Ann".to_string()),
+            since: Some(log.items[1].recorded_at),
+            ..AuditQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(own.total, 2);
+
+    let error = athlete.audit(&AuditQuery::default()).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::FORBIDDEN));
+}
+
+#[tokio::test]
+async fn imports_valid_rows_of_csv() {
+    let url = start_service().await;
+    let coach = Client::new(&url).unwrap().with_api_key("coach-dev-key");
+
+    let report = coach
+        .import_csv(
+            "biathlon",
+            "name,accuracy,distance,speed,recorded_at\n\
+            Ann,90,10,12.5,2025-03-08T10:00:00Z\n\
+            Bob,120,10,11,2025-03-08\n\
+            Eve,80,10,11,\n\
+            Ann,85,12,13,2025-03-09\n",
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.imported, 2);
+    let rejected: Vec<(u64, &str, Vec<&str>)> = report
+        .rejected
+        .iter()
+        .map(|row| {
+            let fields = row.errors.iter().map(|e| e.field.as_str()).collect();
+            (row.line, row.name.as_str(), fields)
+        })
+        .collect();
+    assert_eq!(
+        rejected,
+        [(3, "Bob", vec!["accuracy"]), (4, "Eve", vec!["name"])]
+    );
+
+    let history = coach.history_of::<Value>("biathlon", "Ann").await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].performance["accuracy"], 85.0);
+    let error = coach.performance_of::<Value>("biathlon", "Bob").await;
+    assert_eq!(error.unwrap_err().code(), Some("sportsman_not_found"));
+
+    let log = coach
+        .audit(&AuditQuery {
+            sport: Some("biathlon".to_string()),
+            ..AuditQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(log.total, 2);
+    assert_eq!(log.items[1].before.as_ref().unwrap()["accuracy"], 90.0);
+
+    // backdated row doesn't replace the latest record
+    coach
+        .import_csv(
+            "biathlon",
+            "name,accuracy,distance,speed,recorded_at\nAnn,70,10,12,2025-03-01\n",
+        )
+        .await
+        .unwrap();
+    let log = coach
+        .audit(&AuditQuery {
+            sport: Some("biathlon".to_string()),
+            ..AuditQuery::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(log.total, 3);
+    assert_eq!(log.items[2].before, None);
+
+    let error = coach
+        .import_csv("running", "name,pace\nAnn,5\n")
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), Some("malformed_request"));
+}
@@ -0,0 +1,12 @@
+-- Every recorded session is kept, so one sportsman can have many rows per sport
+ALTER TABLE Running DROP CONSTRAINT IF EXISTS running_sportsman_id_key;
+ALTER TABLE Running ADD COLUMN IF NOT EXISTS recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE INDEX IF NOT EXISTS running_sportsman_recorded_at ON Running (sportsman_id, recorded_at);
+
+ALTER TABLE Biathlon DROP CONSTRAINT IF EXISTS biathlon_sportsman_id_key;
+ALTER TABLE Biathlon ADD COLUMN IF NOT EXISTS recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE INDEX IF NOT EXISTS biathlon_sportsman_recorded_at ON Biathlon (sportsman_id, recorded_at);
+
+ALTER TABLE WeightLifting DROP CONSTRAINT IF EXISTS weightlifting_sportsman_id_key;
+ALTER TABLE WeightLifting ADD COLUMN IF NOT EXISTS recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE INDEX IF NOT EXISTS weight_lifting_sportsman_recorded_at ON WeightLifting (sportsman_id, recorded_at);
@@ -0,0 +1,5 @@
+ALTER TABLE Sportsmen ADD COLUMN IF NOT EXISTS birth_date DATE;
+ALTER TABLE Sportsmen ADD COLUMN IF NOT EXISTS sex VARCHAR(6);
+ALTER TABLE Sportsmen ADD COLUMN IF NOT EXISTS body_mass REAL;
+ALTER TABLE Sportsmen ADD COLUMN IF NOT EXISTS nationality CHAR(3);
+ALTER TABLE Sportsmen ADD COLUMN IF NOT EXISTS club VARCHAR(100);
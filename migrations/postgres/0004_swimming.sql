@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS Swimming
+(
+    ID SERIAL PRIMARY KEY,
+    distance REAL NOT NULL,
+    time REAL NOT NULL,
+    stroke VARCHAR(12) NOT NULL,
+    pool_length REAL NOT NULL,
+    sportsman_id INT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (sportsman_id) REFERENCES Sportsmen(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS swimming_sportsman_recorded_at ON Swimming (sportsman_id, recorded_at);
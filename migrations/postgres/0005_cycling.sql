@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS Cycling
+(
+    ID SERIAL PRIMARY KEY,
+    distance REAL NOT NULL,
+    duration REAL NOT NULL,
+    average_power REAL NOT NULL,
+    average_cadence REAL NOT NULL,
+    elevation_gain REAL NOT NULL,
+    sportsman_id INT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (sportsman_id) REFERENCES Sportsmen(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS cycling_sportsman_recorded_at ON Cycling (sportsman_id, recorded_at);
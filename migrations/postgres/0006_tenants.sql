@@ -0,0 +1,5 @@
+-- Sportsmen belong to a tenant (club), names are unique within the tenant only.
+-- Performances belong to the tenant of their sportsman
+ALTER TABLE Sportsmen ADD COLUMN IF NOT EXISTS tenant VARCHAR(50) NOT NULL DEFAULT 'default';
+ALTER TABLE Sportsmen DROP CONSTRAINT IF EXISTS sportsmen_name_key;
+CREATE UNIQUE INDEX IF NOT EXISTS sportsmen_tenant_name ON Sportsmen (tenant, name);
@@ -0,0 +1,27 @@
+-- Append-only log of the changes of performance data. Sportsmen are kept by name,
+-- so the entries outlive the removed sportsmen. Values are stored records in metric units
+CREATE TABLE IF NOT EXISTS Audit
+(
+    ID BIGSERIAL PRIMARY KEY,
+    tenant VARCHAR(50) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    operation VARCHAR(20) NOT NULL,
+    sport VARCHAR(50) NOT NULL,
+    sportsman VARCHAR(50) NOT NULL,
+    before JSONB,
+    after JSONB,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS audit_tenant_recorded_at ON Audit (tenant, recorded_at);
+
+CREATE OR REPLACE FUNCTION audit_is_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_append_only ON Audit;
+CREATE TRIGGER audit_append_only
+    BEFORE UPDATE OR DELETE ON Audit
+    FOR EACH ROW EXECUTE FUNCTION audit_is_append_only();
@@ -0,0 +1,47 @@
+CREATE TABLE IF NOT EXISTS Sportsmen
+(
+    ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    name VARCHAR(50) UNIQUE NOT NULL,
+    birth_date DATE,
+    sex VARCHAR(6),
+    body_mass REAL,
+    nationality CHAR(3),
+    club VARCHAR(100)
+);
+
+CREATE TABLE IF NOT EXISTS Running
+(
+    ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    distance REAL NOT NULL,
+    speed REAL NOT NULL,
+    sportsman_id INT NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (sportsman_id) REFERENCES Sportsmen(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS running_sportsman_recorded_at ON Running (sportsman_id, recorded_at);
+
+CREATE TABLE IF NOT EXISTS Biathlon
+(
+    ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    accuracy REAL NOT NULL,
+    distance REAL NOT NULL,
+    speed REAL NOT NULL,
+    sportsman_id INT NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (sportsman_id) REFERENCES Sportsmen(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS biathlon_sportsman_recorded_at ON Biathlon (sportsman_id, recorded_at);
+
+CREATE TABLE IF NOT EXISTS WeightLifting
+(
+    ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    weight REAL NOT NULL,
+    lifted_weight REAL NOT NULL,
+    sportsman_id INT NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (sportsman_id) REFERENCES Sportsmen(ID) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS weight_lifting_sportsman_recorded_at ON WeightLifting (sportsman_id, recorded_at);
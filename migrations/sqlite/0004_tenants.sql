@@ -0,0 +1,23 @@
+-- Sportsmen belong to a tenant (club), names are unique within the tenant only.
+-- Performances belong to the tenant of their sportsman.
+-- SQLite can't drop the unique constraint of the name, so the table is rebuilt. Migrations are run
+-- with foreign keys turned off, otherwise dropping the old table would remove the performances
+CREATE TABLE Sportsmen_new
+(
+    ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant VARCHAR(50) NOT NULL DEFAULT 'default',
+    name VARCHAR(50) NOT NULL,
+    birth_date DATE,
+    sex VARCHAR(6),
+    body_mass REAL,
+    nationality CHAR(3),
+    club VARCHAR(100)
+);
+
+INSERT INTO Sportsmen_new (ID, name, birth_date, sex, body_mass, nationality, club)
+SELECT ID, name, birth_date, sex, body_mass, nationality, club FROM Sportsmen;
+
+DROP TABLE Sportsmen;
+ALTER TABLE Sportsmen_new RENAME TO Sportsmen;
+
+CREATE UNIQUE INDEX sportsmen_tenant_name ON Sportsmen (tenant, name);
@@ -0,0 +1,26 @@
+-- Append-only log of the changes of performance data. Sportsmen are kept by name,
+-- so the entries outlive the removed sportsmen. Values are stored records in metric units
+CREATE TABLE IF NOT EXISTS Audit
+(
+    ID INTEGER PRIMARY KEY AUTOINCREMENT,
+    tenant VARCHAR(50) NOT NULL,
+    actor VARCHAR(100) NOT NULL,
+    operation VARCHAR(20) NOT NULL,
+    sport VARCHAR(50) NOT NULL,
+    sportsman VARCHAR(50) NOT NULL,
+    before TEXT,
+    after TEXT,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS audit_tenant_recorded_at ON Audit (tenant, recorded_at);
+
+CREATE TRIGGER IF NOT EXISTS audit_no_update BEFORE UPDATE ON Audit
+BEGIN
+    SELECT RAISE(ABORT, 'audit log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_no_delete BEFORE DELETE ON Audit
+BEGIN
+    SELECT RAISE(ABORT, 'audit log is append-only');
+END;
@@ -1,16 +1,77 @@
-use crate::service::core::Service;
+use athlete_tracker::service::auth::Auth;
+use athlete_tracker::service::config::{Config, StartupError};
+use athlete_tracker::service::core::{Service, Storage};
+use athlete_tracker::service::memory::memory_pool::MemoryPool;
+use athlete_tracker::service::postgres::postgres_pool::DBPool;
+use athlete_tracker::service::registry::SportRegistry;
+use athlete_tracker::service::sqlite::sqlite_pool::SqliteDBPool;
 use dotenv::dotenv;
-
-mod models;
-mod service;
-mod traits;
+use std::env;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     dotenv().ok();
     env_logger::init();
 
-    let _ = Service::new().await.start().await;
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            log::error!("{e}");
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run() -> Result<(), StartupError> {
+    let config = Config::from_env()?;
+    log::info!("Using {} storage", config.storage);
+
+    let registry = SportRegistry::load(config.sports_schema.as_deref())?;
+
+    // storage is created with all migrations applied, only tables of the sports
+    // from the schema are left
+    if env::args().any(|arg| arg == "--migrate-only") {
+        match config.storage {
+            Storage::Postgres => {
+                registry
+                    .create_storages(&DBPool::new(&config.pool).await?)
+                    .await?
+            }
+            Storage::Sqlite => {
+                registry
+                    .create_storages(&SqliteDBPool::new(&config.pool).await?)
+                    .await?
+            }
+            Storage::Memory => log::info!("Memory storage doesn't have migrations"),
+        }
+        return Ok(());
+    }
+
+    // invalid keys file or secret fails the startup before the storage is touched
+    let auth = Auth::load(&config.auth)?;
 
-    Ok(())
+    match config.storage {
+        Storage::Postgres => {
+            let pool = DBPool::new(&config.pool).await?;
+            Service::new(pool, registry, auth, &config)
+                .await?
+                .start()
+                .await
+        }
+        Storage::Sqlite => {
+            let pool = SqliteDBPool::new(&config.pool).await?;
+            Service::new(pool, registry, auth, &config)
+                .await?
+                .start()
+                .await
+        }
+        Storage::Memory => {
+            Service::new(MemoryPool::new(), registry, auth, &config)
+                .await?
+                .start()
+                .await
+        }
+    }
 }
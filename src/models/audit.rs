@@ -0,0 +1,142 @@
+use crate::models::performance_tracker::Record;
+use crate::models::sportsman::Sportsman;
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Change of the performance data kept in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// New record, it replaces the latest one of the sport
+    Add,
+    /// Whole history of the sport is removed
+    Remove,
+    /// History of the sport is removed with the sportsman
+    RemoveSportsman,
+    /// Sportsman with the history of the sport is renamed
+    Rename,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Add => "add",
+            Operation::Remove => "remove",
+            Operation::RemoveSportsman => "remove_sportsman",
+            Operation::Rename => "rename",
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "add" => Ok(Operation::Add),
+            "remove" => Ok(Operation::Remove),
+            "remove_sportsman" => Ok(Operation::RemoveSportsman),
+            "rename" => Ok(Operation::Rename),
+            _ => Err(format!("Unknown audit operation: {s}")),
+        }
+    }
+}
+
+/// Entry of the append-only audit log, stored in the same transaction as the change.
+/// Values are the stored records without the derived fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// Name of the API key or subject of the token that made the change
+    pub actor: String,
+    pub operation: Operation,
+    pub sport: String,
+    pub sportsman: String,
+    /// Latest record replaced by the added one, the removed history or the previous name
+    pub before: Option<Value>,
+    /// Added record or the new name
+    pub after: Option<Value>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    /// Addition of the record, `latest` is the record of the sport it replaces
+    pub fn added(
+        actor: &str,
+        sportsman: &Sportsman,
+        sport: &str,
+        latest: Option<&Record>,
+        record: &Record,
+    ) -> Self {
+        Self {
+            actor: actor.to_string(),
+            operation: Operation::Add,
+            sport: sport.to_string(),
+            sportsman: sportsman.name(),
+            before: latest.map(Record::to_stored_json),
+            after: Some(record.to_stored_json()),
+            recorded_at: record.recorded_at,
+        }
+    }
+
+    /// Removal of the whole history of the sport
+    pub fn removed(
+        operation: Operation,
+        actor: &str,
+        sportsman: &Sportsman,
+        sport: &str,
+        history: &[Record],
+    ) -> Self {
+        Self {
+            actor: actor.to_string(),
+            operation,
+            sport: sport.to_string(),
+            sportsman: sportsman.name(),
+            before: Some(Value::Array(
+                history.iter().map(Record::to_stored_json).collect(),
+            )),
+            after: None,
+            // Postgres keeps timestamps with microsecond precision
+            recorded_at: Utc::now().trunc_subsecs(6),
+        }
+    }
+
+    /// Rename of the sportsman who has records of the sport, the entry is kept under the new name
+    pub fn renamed(actor: &str, sportsman: &Sportsman, updated: &Sportsman, sport: &str) -> Self {
+        Self {
+            actor: actor.to_string(),
+            operation: Operation::Rename,
+            sport: sport.to_string(),
+            sportsman: updated.name(),
+            before: Some(json!({ "name": sportsman.name() })),
+            after: Some(json!({ "name": updated.name() })),
+            recorded_at: Utc::now().trunc_subsecs(6),
+        }
+    }
+}
+
+/// Entries of the tenant matching all the given filters, from the oldest
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub sportsman: Option<String>,
+    pub sport: Option<String>,
+    /// Inclusive
+    pub since: Option<DateTime<Utc>>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.sportsman
+            .as_ref()
+            .is_none_or(|name| *name == entry.sportsman)
+            && self
+                .sport
+                .as_ref()
+                .is_none_or(|sport| *sport == entry.sport)
+            && self.since.is_none_or(|since| entry.recorded_at >= since)
+    }
+}
@@ -1,47 +1,263 @@
-use axum::http::StatusCode;
+use crate::models::validation::FieldError;
+use axum::http::header::{CONTENT_TYPE, WWW_AUTHENTICATE};
+use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Json as AxumJson, Response};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use std::error::Error as StdError;
 use std::fmt::{Display, Formatter};
 use utoipa::ToSchema;
 
-#[derive(Serialize, Deserialize, ToSchema)]
+/// Every error the service reports to clients, rendered as RFC 7807 problem details
+#[derive(Debug)]
 pub enum Error {
     SportsmanNotFound,
-    SportsmanDoesntHasMetric,
-    SaveError,
-    RemoveError,
+    PerformanceNotFound,
+    SportNotFound(String),
+    SportsmanAlreadyExists,
     NameTooLong,
+    /// Name that can't be used in routes, e.g. a blank one
+    InvalidName(String),
+    InvalidProfile(String),
+    InvalidRankingField(Vec<&'static str>),
+    InvalidPerformance {
+        model: &'static str,
+        errors: Vec<FieldError>,
+    },
+    /// Path that matches no route
+    RouteNotFound(String),
+    /// Route exists but doesn't accept the method
+    MethodNotAllowed(String),
+    /// Body or query string that couldn't be parsed
+    MalformedRequest(String),
+    UnsupportedMediaType(String),
+    /// Requested representation can't be produced, e.g. unknown units
+    NotAcceptable(String),
+    /// Missing or invalid credentials
+    Unauthenticated(String),
+    /// Caller's role doesn't allow the request
+    Forbidden(String),
+    /// Database can't be reached right now, the request may be retried
+    StorageUnavailable(sqlx::Error),
+    Storage(sqlx::Error),
+}
+
+impl Error {
+    /// Stable machine-readable code, clients should rely on it rather than on the messages
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::SportsmanNotFound => "sportsman_not_found",
+            Error::PerformanceNotFound => "performance_not_found",
+            Error::SportNotFound(_) => "sport_not_found",
+            Error::SportsmanAlreadyExists => "sportsman_already_exists",
+            Error::NameTooLong => "name_too_long",
+            Error::InvalidName(_) => "invalid_name",
+            Error::InvalidProfile(_) => "invalid_profile",
+            Error::InvalidRankingField(_) => "invalid_ranking_field",
+            Error::InvalidPerformance { .. } => "invalid_performance",
+            Error::RouteNotFound(_) => "route_not_found",
+            Error::MethodNotAllowed(_) => "method_not_allowed",
+            Error::MalformedRequest(_) => "malformed_request",
+            Error::UnsupportedMediaType(_) => "unsupported_media_type",
+            Error::NotAcceptable(_) => "not_acceptable",
+            Error::Unauthenticated(_) => "unauthenticated",
+            Error::Forbidden(_) => "forbidden",
+            Error::StorageUnavailable(_) => "storage_unavailable",
+            Error::Storage(_) => "storage_error",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::SportsmanNotFound
+            | Error::PerformanceNotFound
+            | Error::SportNotFound(_)
+            | Error::RouteNotFound(_) => StatusCode::NOT_FOUND,
+            Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            Error::SportsmanAlreadyExists => StatusCode::CONFLICT,
+            Error::NameTooLong
+            | Error::InvalidName(_)
+            | Error::InvalidProfile(_)
+            | Error::InvalidRankingField(_)
+            | Error::MalformedRequest(_) => StatusCode::BAD_REQUEST,
+            Error::InvalidPerformance { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Error::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            Error::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+            Error::StorageUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Error::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Short summary that is the same for every occurrence of the code
+    pub fn title(&self) -> &'static str {
+        match self {
+            Error::SportsmanNotFound => "Sportsman not found",
+            Error::PerformanceNotFound => "Performance not found",
+            Error::SportNotFound(_) => "Sport not found",
+            Error::SportsmanAlreadyExists => "Sportsman already exists",
+            Error::NameTooLong => "Sportsman name is too long",
+            Error::InvalidName(_) => "Invalid sportsman name",
+            Error::InvalidProfile(_) => "Invalid profile",
+            Error::InvalidRankingField(_) => "Invalid ranking field",
+            Error::InvalidPerformance { .. } => "Invalid performance",
+            Error::RouteNotFound(_) => "Route not found",
+            Error::MethodNotAllowed(_) => "Method not allowed",
+            Error::MalformedRequest(_) => "Malformed request",
+            Error::UnsupportedMediaType(_) => "Unsupported media type",
+            Error::NotAcceptable(_) => "Not acceptable",
+            Error::Unauthenticated(_) => "Authentication required",
+            Error::Forbidden(_) => "Forbidden",
+            Error::StorageUnavailable(_) => "Storage unavailable",
+            Error::Storage(_) => "Something went wrong",
+        }
+    }
+
+    /// Details of the storage errors stay in logs
+    pub fn problem(&self) -> Problem {
+        let detail = match self {
+            Error::StorageUnavailable(_) => {
+                "Storage is temporarily unavailable, try again later".to_string()
+            }
+            Error::Storage(_) => "Something went wrong".to_string(),
+            _ => self.to_string(),
+        };
+
+        Problem {
+            problem_type: format!("urn:athlete-tracker:problem:{}", self.code()),
+            title: self.title().to_string(),
+            status: self.status().as_u16(),
+            detail,
+            code: self.code().to_string(),
+            instance: None,
+            request_id: None,
+            errors: match self {
+                Error::InvalidPerformance { errors, .. } => errors.clone(),
+                _ => Vec::new(),
+            },
+            expected: match self {
+                Error::InvalidRankingField(expected) => {
+                    expected.iter().map(|field| field.to_string()).collect()
+                }
+                _ => Vec::new(),
+            },
+            cause: match self {
+                Error::StorageUnavailable(e) | Error::Storage(e) => Some(e.to_string()),
+                _ => None,
+            },
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::SportsmanNotFound => write!(f, "Sportsman not found"),
-            Error::SportsmanDoesntHasMetric => write!(f, "Metric wasn't found"),
-            Error::SaveError => write!(f, "Something went wrong"),
-            Error::RemoveError => write!(f, "Something went wrong"),
+            Error::PerformanceNotFound => write!(f, "Performance not found"),
+            Error::SportNotFound(sport) => write!(f, "Unknown sport {sport}"),
+            Error::SportsmanAlreadyExists => write!(f, "Sportsman already exists"),
             Error::NameTooLong => write!(f, "Sportsman name is too long"),
+            Error::InvalidName(reason) => write!(f, "Invalid sportsman name: {reason}"),
+            Error::InvalidProfile(reason) => write!(f, "Invalid profile: {}", reason),
+            Error::InvalidRankingField(expected) => write!(
+                f,
+                "Invalid ranking field, expected one of: {}",
+                expected.join(", ")
+            ),
+            Error::InvalidPerformance { model, errors } => match errors.len() {
+                1 => write!(f, "Invalid {model}: 1 invalid field"),
+                count => write!(f, "Invalid {model}: {count} invalid fields"),
+            },
+            Error::RouteNotFound(path) => write!(f, "No route for {path}"),
+            Error::MethodNotAllowed(method) => write!(f, "Method {method} is not allowed here"),
+            Error::MalformedRequest(reason) => write!(f, "Malformed request: {reason}"),
+            Error::UnsupportedMediaType(reason) => write!(f, "{reason}"),
+            Error::NotAcceptable(reason) => write!(f, "Not acceptable: {reason}"),
+            Error::Unauthenticated(reason) | Error::Forbidden(reason) => write!(f, "{reason}"),
+            Error::StorageUnavailable(e) => write!(f, "Storage unavailable: {e}"),
+            Error::Storage(e) => write!(f, "Storage error: {e}"),
         }
     }
 }
 
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::StorageUnavailable(e) | Error::Storage(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that mean the database can't be reached are told apart from the failed queries
+impl From<sqlx::Error> for Error {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+                Error::StorageUnavailable(e)
+            }
+            _ => Error::Storage(e),
+        }
+    }
+}
+
+/// Authentication errors tell the accepted scheme in `WWW-Authenticate` header
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        let status = match self {
-            Error::SportsmanNotFound => StatusCode::NOT_FOUND,
-            Error::SportsmanDoesntHasMetric => StatusCode::NOT_FOUND,
-            Error::SaveError => StatusCode::INTERNAL_SERVER_ERROR,
-            Error::RemoveError => StatusCode::INTERNAL_SERVER_ERROR,
-            Error::NameTooLong => StatusCode::BAD_REQUEST,
-        };
+        let mut response = self.problem().into_response();
+        if let Error::Unauthenticated(_) = self {
+            response.headers_mut().insert(
+                WWW_AUTHENTICATE,
+                HeaderValue::from_static("Bearer realm=\"athlete-tracker\""),
+            );
+        }
+        response
+    }
+}
+
+/// RFC 7807 problem details, `code` and `request_id` are extension members.
+/// Request id and path are filled by the request id middleware
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Invalid fields of the request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+    /// Accepted values, e.g. ranking fields
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected: Vec<String>,
+    /// Internal error that is logged but not shown to clients
+    #[serde(skip)]
+    #[schema(ignore)]
+    pub cause: Option<String>,
+}
 
-        (
-            status,
-            AxumJson(json!({
-                "message": self.to_string(),
-            })),
-        )
-            .into_response()
+impl Problem {
+    pub fn render(&self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (status, AxumJson(self)).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        response
+    }
+}
+
+/// Problem is kept in the response extensions, so the middleware can complete it
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let mut response = self.render();
+        response.extensions_mut().insert(self);
+        response
     }
 }
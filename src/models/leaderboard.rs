@@ -0,0 +1,136 @@
+use crate::models::performance_tracker::Record;
+use crate::models::sportsman::Sportsman;
+use crate::models::units::{Unit, UnitSystem, Units};
+use crate::service::models::{Order, Ranking, RankingEntry};
+use axum::response::{IntoResponse, Json as AxumJson, Response};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub sportsman: Sportsman,
+    pub value: f32,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct Leaderboard {
+    sport: String,
+    by: String,
+    descending: bool,
+    /// Unit of the values, None for unitless fields
+    unit: Option<Unit>,
+    entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Ranks sportsmen by their best value of the field over the whole history.
+    /// Sportsmen with equal values share the rank and are ordered by name,
+    /// so the next rank is skipped ("1, 2, 2, 4")
+    pub fn new(
+        sport: String,
+        by: String,
+        descending: bool,
+        limit: usize,
+        histories: Vec<(Sportsman, Vec<Record>)>,
+    ) -> Self {
+        let is_better = |candidate: f32, best: f32| {
+            if descending {
+                candidate > best
+            } else {
+                candidate < best
+            }
+        };
+
+        let mut best: Vec<(Sportsman, f32, DateTime<Utc>)> = histories
+            .into_iter()
+            .filter_map(|(sportsman, history)| {
+                history
+                    .iter()
+                    .filter_map(|r| {
+                        r.metric
+                            .field_for(&by, sportsman.profile())
+                            .filter(|v| !v.is_nan())
+                            .map(|v| (v, r.recorded_at))
+                    })
+                    .reduce(|best, candidate| {
+                        if is_better(candidate.0, best.0) {
+                            candidate
+                        } else {
+                            best
+                        }
+                    })
+                    .map(|(value, recorded_at)| (sportsman, value, recorded_at))
+            })
+            .collect();
+
+        best.sort_by(|a, b| {
+            let by_value = a.1.total_cmp(&b.1);
+            let by_value = if descending {
+                by_value.reverse()
+            } else {
+                by_value
+            };
+            by_value.then_with(|| a.0.name().cmp(&b.0.name()))
+        });
+
+        let mut entries: Vec<LeaderboardEntry> = Vec::with_capacity(best.len().min(limit));
+        for (ind, (sportsman, value, recorded_at)) in best.into_iter().take(limit).enumerate() {
+            let rank = match entries.last() {
+                Some(prev) if prev.value == value => prev.rank,
+                _ => ind + 1,
+            };
+            entries.push(LeaderboardEntry {
+                rank,
+                sportsman,
+                value,
+                recorded_at,
+            });
+        }
+
+        Self {
+            sport,
+            by,
+            descending,
+            unit: None,
+            entries,
+        }
+    }
+
+    /// Converts values of the measured field, conversion keeps the order and the ties
+    pub fn in_units(mut self, units: Option<Units>, system: UnitSystem) -> Self {
+        if let Some(units) = units {
+            for entry in &mut self.entries {
+                entry.value = units.show(entry.value, system);
+            }
+            self.unit = Some(units.shown_in(system));
+        }
+        self
+    }
+}
+
+impl IntoResponse for Leaderboard {
+    fn into_response(self) -> Response {
+        AxumJson(Ranking {
+            sport: self.sport,
+            by: self.by,
+            order: if self.descending {
+                Order::Desc
+            } else {
+                Order::Asc
+            },
+            unit: self.unit.map(|unit| unit.symbol().to_string()),
+            entries: self
+                .entries
+                .into_iter()
+                .map(|e| RankingEntry {
+                    rank: e.rank,
+                    name: e.sportsman.name(),
+                    value: e.value,
+                    recorded_at: e.recorded_at,
+                })
+                .collect(),
+        })
+        .into_response()
+    }
+}
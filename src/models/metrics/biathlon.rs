@@ -1,26 +1,44 @@
-use crate::traits::traits::Metric;
+use crate::models::metrics::serialize_f32;
+use crate::models::units::{Unit, Units};
+use crate::traits::traits::{Metric, Quantity};
 use axum::response::{IntoResponse, Json as AxumJson, Response};
-use serde_json::json;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::fmt::Debug;
 use utoipa::ToSchema;
 
-#[derive(Debug, Clone, ToSchema)]
-pub struct Accuracy(pub f32);
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Accuracy(#[serde(serialize_with = "serialize_f32")] pub f32);
 
-#[derive(Debug, Clone, ToSchema)]
-pub struct Distance(pub f32);
+/// Distance in km
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Distance(#[serde(serialize_with = "serialize_f32")] pub f32);
 
-#[derive(Debug, Clone, ToSchema)]
-pub struct Speed(pub f32);
+impl Quantity for Distance {
+    const UNITS: Units = Units::of(Unit::Kilometer);
+}
+
+/// Speed in km per hour
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Speed(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+impl Quantity for Speed {
+    const UNITS: Units = Units::of(Unit::KilometersPerHour);
+}
 
-#[derive(Debug, Clone, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Biathlon {
     /// Shooting accuracy
+    #[schema(inline)]
     pub accuracy: Accuracy,
     /// Distance in km
+    #[schema(inline)]
     pub distance: Distance,
     /// Speed in km per hour
+    #[schema(inline)]
     pub speed: Speed,
 }
 
@@ -36,12 +54,7 @@ impl Biathlon {
 
 impl IntoResponse for Biathlon {
     fn into_response(self) -> Response {
-        AxumJson(json!({
-            "accuracy": self.accuracy.0,
-            "distance": self.distance.0,
-            "speed:": self.speed.0,
-        }))
-        .into_response()
+        AxumJson(self).into_response()
     }
 }
 
@@ -56,4 +69,20 @@ impl Metric for Biathlon {
     fn response_name(&self) -> &'static str {
         "Biathlon"
     }
+
+    fn sport_name(&self) -> &'static str {
+        "biathlon"
+    }
+
+    fn ranking_fields() -> &'static [&'static str] {
+        &["accuracy", "distance", "speed"]
+    }
+
+    fn field_units() -> &'static [(&'static str, Units)] {
+        &[("distance", Distance::UNITS), ("speed", Speed::UNITS)]
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Metric fields are plain numbers and strings")
+    }
 }
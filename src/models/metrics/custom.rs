@@ -0,0 +1,311 @@
+use crate::models::metrics::widen;
+use crate::models::validation::{Checks, FieldError};
+use crate::traits::traits::Metric;
+use axum::response::{IntoResponse, Json as AxumJson, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+/// Route segments taken by the service itself
+const RESERVED_NAMES: &[&str] = &[
+    "sportsmen",
+    "health",
+    "swagger",
+    "api-docs",
+    "audit",
+    "import",
+];
+/// Route segments after the sport, e.g. `/{sport}/leaderboard`, so they can't be sportsman names
+pub const RESERVED_SPORTSMAN_NAMES: &[&str] = &["leaderboard"];
+/// Columns every sport table has besides the fields
+const RESERVED_FIELDS: &[&str] = &["id", "sportsman_id", "recorded_at"];
+
+/// File listing sports defined at runtime, see `SPORTS_SCHEMA`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SportsSchema {
+    pub sports: Vec<SportSchema>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SportSchema {
+    /// Name used in routes and in the table name, e.g. `rowing`
+    pub name: String,
+    /// Name used in responses, `name` by default
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldSchema {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    pub unit: Option<String>,
+    pub description: Option<String>,
+    /// Inclusive bounds of numeric fields
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// Allowed values of text fields, any text if empty
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Number,
+    Integer,
+    Text,
+}
+
+impl FieldType {
+    pub fn is_numeric(self) -> bool {
+        self != FieldType::Text
+    }
+}
+
+impl SportSchema {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Checks that names can be used as route segment and sql identifiers
+    /// and that bounds and allowed values make sense
+    pub fn check(&self) -> Result<(), String> {
+        if !is_identifier(&self.name) {
+            return Err(format!(
+                "sport name {:?} must consist of lowercase latin letters, digits and underscores",
+                self.name
+            ));
+        }
+        if RESERVED_NAMES.contains(&self.name.as_str()) {
+            return Err(format!("sport name {:?} is reserved", self.name));
+        }
+        if self.fields.is_empty() {
+            return Err(format!("sport {} has no fields", self.name));
+        }
+
+        let mut names = HashSet::new();
+        for field in &self.fields {
+            if !is_identifier(&field.name) {
+                return Err(format!(
+                    "field name {:?} of {} must consist of lowercase latin letters, digits and underscores",
+                    field.name, self.name
+                ));
+            }
+            if RESERVED_FIELDS.contains(&field.name.as_str()) {
+                return Err(format!(
+                    "field name {:?} of {} is reserved",
+                    field.name, self.name
+                ));
+            }
+            if !names.insert(field.name.as_str()) {
+                return Err(format!(
+                    "field {} of {} is defined twice",
+                    field.name, self.name
+                ));
+            }
+            if field.field_type.is_numeric() && !field.values.is_empty() {
+                return Err(format!(
+                    "field {} of {} is numeric and can't list allowed values",
+                    field.name, self.name
+                ));
+            }
+            if !field.field_type.is_numeric() && (field.min.is_some() || field.max.is_some()) {
+                return Err(format!(
+                    "field {} of {} is text and can't have min or max",
+                    field.name, self.name
+                ));
+            }
+            if let (Some(min), Some(max)) = (field.min, field.max) {
+                if min > max {
+                    return Err(format!(
+                        "min of field {} of {} is greater than max",
+                        field.name, self.name
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns fields that are present and have the right type along with errors of the rest,
+    /// unknown fields are rejected. Numbers are stored as `f32`, so they are rounded the same way here
+    pub fn read(&self, value: Value) -> (Map<String, Value>, Vec<FieldError>) {
+        let Value::Object(mut object) = value else {
+            return (
+                Map::new(),
+                vec![FieldError::new("body", "must be an object")],
+            );
+        };
+
+        let mut fields = Map::new();
+        let mut errors = Vec::new();
+        for field in &self.fields {
+            let value = match object.remove(&field.name) {
+                None | Some(Value::Null) => {
+                    errors.push(FieldError::new(&field.name, "is required"));
+                    continue;
+                }
+                Some(value) => value,
+            };
+
+            let value = match field.field_type {
+                FieldType::Number => value
+                    .as_f64()
+                    .map(|v| v as f32)
+                    .filter(|v| v.is_finite())
+                    .map(|v| json!(widen(v))),
+                FieldType::Integer => value
+                    .as_f64()
+                    .filter(|v| v.fract() == 0.0)
+                    .map(|v| json!(v as i64)),
+                FieldType::Text => value.is_string().then_some(value),
+            };
+            match value {
+                Some(value) => {
+                    fields.insert(field.name.clone(), value);
+                }
+                None => errors.push(FieldError::new(
+                    &field.name,
+                    match field.field_type {
+                        FieldType::Number => "must be a number",
+                        FieldType::Integer => "must be an integer",
+                        FieldType::Text => "must be a string",
+                    },
+                )),
+            }
+        }
+        errors.extend(
+            object
+                .keys()
+                .map(|unknown| FieldError::new(unknown, "is unknown")),
+        );
+
+        (fields, errors)
+    }
+
+    /// Checks bounds and allowed values, applied to requests only,
+    /// so changing them doesn't break loading of stored records
+    pub fn validate(&self, fields: &Map<String, Value>) -> Result<(), Vec<FieldError>> {
+        let mut checks = Checks::new();
+        for field in &self.fields {
+            let Some(value) = fields.get(&field.name) else {
+                continue;
+            };
+
+            if let Some(number) = value.as_f64() {
+                if field.min.is_some_and(|min| number < min)
+                    || field.max.is_some_and(|max| number > max)
+                {
+                    checks = checks.error(
+                        &field.name,
+                        &format!("must be {}", field.range_description()),
+                    );
+                }
+            }
+            if let Some(text) = value.as_str() {
+                if !field.values.is_empty() && !field.values.iter().any(|v| v == text) {
+                    checks = checks.error(
+                        &field.name,
+                        &format!("must be one of: {}", field.values.join(", ")),
+                    );
+                }
+            }
+        }
+
+        checks.finish()
+    }
+}
+
+impl FieldSchema {
+    fn range_description(&self) -> String {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => format!("between {min} and {max}"),
+            (Some(min), None) => format!("at least {min}"),
+            (None, Some(max)) => format!("at most {max}"),
+            (None, None) => "a number".to_string(),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.len() <= 48
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Record of a sport defined by [`SportSchema`], fields are already checked against the schema.
+/// Serialized as the map of the fields, it is read by [`SportSchema::read`] as it needs the schema
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomMetric {
+    #[serde(skip)]
+    sport_name: &'static str,
+    #[serde(skip)]
+    response_name: &'static str,
+    #[serde(flatten)]
+    fields: Map<String, Value>,
+}
+
+impl CustomMetric {
+    pub fn new(
+        sport_name: &'static str,
+        response_name: &'static str,
+        fields: Map<String, Value>,
+    ) -> Self {
+        Self {
+            sport_name,
+            response_name,
+            fields,
+        }
+    }
+
+    pub fn fields(&self) -> &Map<String, Value> {
+        &self.fields
+    }
+}
+
+impl IntoResponse for CustomMetric {
+    fn into_response(self) -> Response {
+        AxumJson(self).into_response()
+    }
+}
+
+impl Metric for CustomMetric {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_box(&self) -> Box<dyn Metric> {
+        Box::new(self.clone())
+    }
+
+    fn response_name(&self) -> &'static str {
+        self.response_name
+    }
+
+    fn sport_name(&self) -> &'static str {
+        self.sport_name
+    }
+
+    /// Ranking fields depend on the schema, they are listed by the sport in the registry
+    fn ranking_fields() -> &'static [&'static str] {
+        &[]
+    }
+
+    fn field(&self, name: &str) -> Option<f32> {
+        self.fields.get(name)?.as_f64().map(|v| v as f32)
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Object(self.fields.clone())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::models::metrics::serialize_f32;
+use crate::models::sportsman::Profile;
+use crate::models::units::{Unit, Units};
+use crate::traits::traits::{Metric, Quantity};
+use axum::response::{IntoResponse, Json as AxumJson, Response};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt::Debug;
+use utoipa::ToSchema;
+
+/// Distance in km
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Distance(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+impl Quantity for Distance {
+    const UNITS: Units = Units::of(Unit::Kilometer);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Duration(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Power(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Cadence(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+/// Elevation gain in meters
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct ElevationGain(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+impl Quantity for ElevationGain {
+    const UNITS: Units = Units::of(Unit::Meter);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Cycling {
+    /// Distance in km
+    #[schema(inline)]
+    pub distance: Distance,
+    /// Duration in seconds
+    #[schema(value_type = f32)]
+    pub duration: Duration,
+    /// Average power in watts
+    #[schema(inline)]
+    pub average_power: Power,
+    /// Average cadence in revolutions per minute
+    #[schema(inline)]
+    pub average_cadence: Cadence,
+    /// Elevation gain in meters
+    #[schema(inline)]
+    pub elevation_gain: ElevationGain,
+}
+
+impl Cycling {
+    /// Average speed is in km per hour as well as speeds of other sports
+    const AVERAGE_SPEED_UNITS: Units = Units::of(Unit::KilometersPerHour);
+
+    pub fn new(
+        distance: Distance,
+        duration: Duration,
+        average_power: Power,
+        average_cadence: Cadence,
+        elevation_gain: ElevationGain,
+    ) -> Self {
+        Self {
+            distance,
+            duration,
+            average_power,
+            average_cadence,
+            elevation_gain,
+        }
+    }
+
+    /// Average speed in km per hour, None for zero duration
+    pub fn average_speed(&self) -> Option<f32> {
+        (self.duration.0 > 0.0).then(|| self.distance.0 / self.duration.0 * 3600.0)
+    }
+}
+
+impl IntoResponse for Cycling {
+    fn into_response(self) -> Response {
+        AxumJson(self).into_response()
+    }
+}
+
+impl Metric for Cycling {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_box(&self) -> Box<dyn Metric> {
+        Box::new(self.clone())
+    }
+
+    fn response_name(&self) -> &'static str {
+        "Cycling"
+    }
+
+    fn sport_name(&self) -> &'static str {
+        "cycling"
+    }
+
+    fn ranking_fields() -> &'static [&'static str] {
+        &[
+            "distance",
+            "duration",
+            "average_power",
+            "average_cadence",
+            "elevation_gain",
+            "average_speed",
+            "watts_per_kg",
+        ]
+    }
+
+    fn field_units() -> &'static [(&'static str, Units)] {
+        &[
+            ("distance", Distance::UNITS),
+            ("elevation_gain", ElevationGain::UNITS),
+            ("average_speed", Self::AVERAGE_SPEED_UNITS),
+        ]
+    }
+
+    fn derived_field_docs() -> &'static [(&'static str, &'static str)] {
+        &[
+            ("average_speed", "Average speed, km/h"),
+            (
+                "watts_per_kg",
+                "Average power per kg of body mass, present when body mass is known",
+            ),
+        ]
+    }
+
+    /// Power to body mass ratio is known only for sportsmen with body mass in the profile
+    fn derived_fields(&self, profile: &Profile) -> Vec<(&'static str, f32)> {
+        let average_speed = self.average_speed().map(|speed| ("average_speed", speed));
+        let watts_per_kg = profile
+            .body_mass
+            .filter(|mass| *mass > 0.0)
+            .map(|mass| ("watts_per_kg", self.average_power.0 / mass));
+
+        average_speed.into_iter().chain(watts_per_kg).collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Metric fields are plain numbers and strings")
+    }
+}
@@ -1,7 +1,11 @@
 use crate::traits::traits::Metric;
+use serde::Serializer;
 
 pub mod biathlon;
+pub mod custom;
+pub mod cycling;
 pub mod running;
+pub mod swimming;
 pub mod weight_lifting;
 
 impl Clone for Box<dyn Metric> {
@@ -9,3 +13,14 @@ impl Clone for Box<dyn Metric> {
         self.clone_box()
     }
 }
+
+/// Shortest decimal form of the value. `f32` widened to `f64` as is keeps its binary error,
+/// e.g. 999.9 would be shown as 999.9000244140625
+pub fn widen(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value as f64)
+}
+
+/// Serializes the value as [`widen`] does, so json values and responses show it as it was sent
+pub fn serialize_f32<S: Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(widen(*value))
+}
@@ -0,0 +1,104 @@
+use crate::models::metrics::serialize_f32;
+use crate::models::units::{Unit, Units};
+use crate::traits::traits::{Metric, Quantity};
+use axum::response::{IntoResponse, Json as AxumJson, Response};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt::Debug;
+use utoipa::ToSchema;
+
+/// Distance in meters, shown in yards in imperial units
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Distance(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+impl Quantity for Distance {
+    const UNITS: Units = Units::of(Unit::Meter).imperial(Unit::Yard);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Time(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+/// Pool length in meters, shown in yards in imperial units
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct PoolLength(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+impl Quantity for PoolLength {
+    const UNITS: Units = Units::of(Unit::Meter).imperial(Unit::Yard);
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Stroke {
+    #[default]
+    Freestyle,
+    Backstroke,
+    Breaststroke,
+    Butterfly,
+    Medley,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Swimming {
+    /// Distance in meters
+    #[schema(inline)]
+    pub distance: Distance,
+    /// Time in seconds
+    #[schema(inline)]
+    pub time: Time,
+    pub stroke: Stroke,
+    /// Pool length in meters, usually 25 or 50
+    #[schema(inline)]
+    pub pool_length: PoolLength,
+}
+
+impl Swimming {
+    pub fn new(distance: Distance, time: Time, stroke: Stroke, pool_length: PoolLength) -> Self {
+        Self {
+            distance,
+            time,
+            stroke,
+            pool_length,
+        }
+    }
+}
+
+impl IntoResponse for Swimming {
+    fn into_response(self) -> Response {
+        AxumJson(self).into_response()
+    }
+}
+
+impl Metric for Swimming {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_box(&self) -> Box<dyn Metric> {
+        Box::new(self.clone())
+    }
+
+    fn response_name(&self) -> &'static str {
+        "Swimming"
+    }
+
+    fn sport_name(&self) -> &'static str {
+        "swimming"
+    }
+
+    fn ranking_fields() -> &'static [&'static str] {
+        &["distance", "time", "pool_length"]
+    }
+
+    fn field_units() -> &'static [(&'static str, Units)] {
+        &[
+            ("distance", Distance::UNITS),
+            ("pool_length", PoolLength::UNITS),
+        ]
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Metric fields are plain numbers and strings")
+    }
+}
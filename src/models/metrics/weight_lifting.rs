@@ -1,21 +1,37 @@
-use crate::traits::traits::Metric;
+use crate::models::metrics::serialize_f32;
+use crate::models::units::{Unit, Units};
+use crate::traits::traits::{Metric, Quantity};
 use axum::response::{IntoResponse, Json as AxumJson, Response};
-use serde_json::json;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::fmt::Debug;
 use utoipa::ToSchema;
 
-#[derive(Debug, Clone, ToSchema)]
-pub struct Weight(pub f32);
+/// Weight in kg
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct Weight(#[serde(serialize_with = "serialize_f32")] pub f32);
 
-#[derive(Debug, Clone, ToSchema)]
-pub struct LiftedWeight(pub f32);
+impl Quantity for Weight {
+    const UNITS: Units = Units::of(Unit::Kilogram);
+}
+
+/// Weight in kg
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+pub struct LiftedWeight(#[serde(serialize_with = "serialize_f32")] pub f32);
+
+impl Quantity for LiftedWeight {
+    const UNITS: Units = Units::of(Unit::Kilogram);
+}
 
-#[derive(Debug, Clone, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct WeightLifting {
-    /// Own weight
+    /// Own weight in kg
+    #[schema(inline)]
     pub weight: Weight,
-    /// Summary lifted weight
+    /// Summary lifted weight in kg
+    #[schema(inline)]
     pub lifted_weight: LiftedWeight,
 }
 
@@ -30,11 +46,7 @@ impl WeightLifting {
 
 impl IntoResponse for WeightLifting {
     fn into_response(self) -> Response {
-        AxumJson(json!({
-            "weight": self.weight.0,
-            "lifted_weight": self.lifted_weight.0,
-        }))
-        .into_response()
+        AxumJson(self).into_response()
     }
 }
 
@@ -49,4 +61,23 @@ impl Metric for WeightLifting {
     fn response_name(&self) -> &'static str {
         "WeightLifting"
     }
+
+    fn sport_name(&self) -> &'static str {
+        "weight_lifting"
+    }
+
+    fn ranking_fields() -> &'static [&'static str] {
+        &["weight", "lifted_weight"]
+    }
+
+    fn field_units() -> &'static [(&'static str, Units)] {
+        &[
+            ("weight", Weight::UNITS),
+            ("lifted_weight", LiftedWeight::UNITS),
+        ]
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Metric fields are plain numbers and strings")
+    }
 }
@@ -1,5 +1,10 @@
+pub mod audit;
 pub mod error;
+pub mod leaderboard;
 pub mod metrics;
 pub mod performance_tracker;
 pub mod responses;
 pub mod sportsman;
+pub mod tenant;
+pub mod units;
+pub mod validation;
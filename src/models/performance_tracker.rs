@@ -1,72 +1,300 @@
 use crate::models::error::Error;
 use crate::models::sportsman::Sportsman;
+use crate::models::tenant::Tenant;
 use crate::traits::traits::{Metric, SportPerformance};
-use std::any::TypeId;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 
-pub type Metrics = Vec<Box<dyn Metric>>;
-type Performances = RwLock<HashMap<Sportsman, Metrics>>;
+/// Single recorded session of some metric
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub metric: Box<dyn Metric>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl Record {
+    pub fn new(metric: Box<dyn Metric>, recorded_at: DateTime<Utc>) -> Self {
+        Self {
+            metric,
+            recorded_at,
+        }
+    }
+
+    /// Includes the fields derived from the sportsman's profile
+    pub fn to_json(&self, sportsman: &Sportsman) -> serde_json::Value {
+        let mut json = self.metric.to_json_for(sportsman.profile());
+        if let Some(object) = json.as_object_mut() {
+            object.insert(
+                "recorded_at".to_string(),
+                serde_json::Value::String(self.recorded_at.to_rfc3339()),
+            );
+        }
+        json
+    }
+
+    /// Record as it is stored, without the derived fields
+    pub fn to_stored_json(&self) -> serde_json::Value {
+        let mut json = self.metric.to_json();
+        if let Some(object) = json.as_object_mut() {
+            object.insert(
+                "recorded_at".to_string(),
+                serde_json::Value::String(self.recorded_at.to_rfc3339()),
+            );
+        }
+        json
+    }
+}
+
+/// Sportsman with the list of sports they have records in
+#[derive(Debug, Clone)]
+pub struct SportsmanSummary {
+    pub sportsman: Sportsman,
+    pub sports: Vec<&'static str>,
+    pub last_recorded_at: Option<DateTime<Utc>>,
+}
 
+/// Records are kept ordered by `recorded_at`
+pub type Metrics = Vec<Record>;
+/// Sportsmen of one tenant with their records
+pub type Sportsmen = HashMap<Sportsman, Metrics>;
+
+/// Cached records of all tenants, every tenant has own [`Partition`] with own lock
 #[derive(Debug)]
 pub struct PerformanceTracker {
-    performances: Performances,
+    partitions: RwLock<HashMap<Tenant, Arc<Partition>>>,
+    /// Set by [`PerformanceTracker::mark_loaded`] once the stored records are in the cache
+    loaded: AtomicBool,
 }
 
 impl PerformanceTracker {
-    pub fn new(sportsmen_to_metrics: HashMap<Sportsman, Metrics>) -> Self {
+    pub fn new(tenants: HashMap<Tenant, Sportsmen>) -> Self {
+        let partitions = tenants
+            .into_iter()
+            .map(|(tenant, sportsmen)| (tenant, Arc::new(Partition::new(sportsmen))))
+            .collect();
+
         Self {
-            performances: RwLock::new(sportsmen_to_metrics),
+            partitions: RwLock::new(partitions),
+            loaded: AtomicBool::new(false),
         }
     }
+
+    pub fn mark_loaded(&self) {
+        self.loaded.store(true, Ordering::Release);
+    }
+
+    /// Checked by the readiness probe
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.load(Ordering::Acquire)
+    }
+
+    /// Records of the tenant, tenant without records gets an empty partition
+    pub async fn partition(&self, tenant: &Tenant) -> Arc<Partition> {
+        if let Some(partition) = self.partitions.read().await.get(tenant) {
+            return Arc::clone(partition);
+        }
+
+        let mut partitions = self.partitions.write().await;
+        Arc::clone(partitions.entry(tenant.clone()).or_default())
+    }
+
+    /// Sportsmen of all tenants
+    pub async fn sportsmen_count(&self) -> usize {
+        let partitions: Vec<Arc<Partition>> =
+            self.partitions.read().await.values().cloned().collect();
+
+        let mut count = 0;
+        for partition in partitions {
+            count += partition.performances.read().await.len();
+        }
+        count
+    }
 }
 
-impl SportPerformance for PerformanceTracker {
-    /// if you add performance it can replace "old" performance that has the same type
-    async fn add_performance(&self, sportsman: Sportsman, metric: Box<dyn Metric>) {
+/// Records of one tenant, sportsmen of other tenants aren't visible through it
+#[derive(Debug, Default)]
+pub struct Partition {
+    performances: RwLock<Sportsmen>,
+}
+
+impl Partition {
+    fn new(sportsmen: Sportsmen) -> Self {
+        Self {
+            performances: RwLock::new(sportsmen),
+        }
+    }
+}
+
+impl SportPerformance for Partition {
+    /// Every added record is kept, so sportsman's history isn't lost
+    async fn add_performance(&self, sportsman: Sportsman, record: Record) {
         let mut perf_guard = self.performances.write().await;
 
         let existing_metrics = perf_guard.entry(sportsman).or_insert_with(Vec::new);
 
-        for existing_metric in existing_metrics.iter_mut() {
-            if existing_metric.as_any().type_id() == metric.as_any().type_id() {
-                *existing_metric = metric;
-                return;
-            }
-        }
+        let ind = existing_metrics.partition_point(|r| r.recorded_at <= record.recorded_at);
+        existing_metrics.insert(ind, record);
+    }
 
-        existing_metrics.push(metric);
+    /// Returns the latest record of the sport
+    async fn get_performance(&self, sportsman: &Sportsman, sport: &str) -> Result<Record, Error> {
+        let perf_guard = self.performances.read().await;
+
+        if let Some(metrics) = perf_guard.get(sportsman) {
+            metrics
+                .iter()
+                .rev()
+                .find(|r| r.metric.sport_name() == sport)
+                .cloned()
+                .ok_or(Error::PerformanceNotFound)
+        } else {
+            Err(Error::SportsmanNotFound)
+        }
     }
 
-    async fn get_performance<T: Metric + Clone>(&self, sportsman: &Sportsman) -> Result<T, Error> {
+    /// Returns all records of the sport from the oldest to the latest
+    async fn get_history(&self, sportsman: &Sportsman, sport: &str) -> Result<Vec<Record>, Error> {
         let perf_guard = self.performances.read().await;
 
         if let Some(metrics) = perf_guard.get(sportsman) {
-            for metric in metrics.iter() {
-                if let Some(down_casted) = metric.as_any().downcast_ref::<T>() {
-                    return Ok(down_casted.clone());
-                }
+            let history: Vec<Record> = metrics
+                .iter()
+                .filter(|r| r.metric.sport_name() == sport)
+                .cloned()
+                .collect();
+
+            if history.is_empty() {
+                return Err(Error::PerformanceNotFound);
             }
-            Err(Error::SportsmanDoesntHasMetric)
+            Ok(history)
         } else {
             Err(Error::SportsmanNotFound)
         }
     }
 
-    async fn remove_performance<T: Metric>(&self, sportsman: Sportsman) -> Result<(), Error> {
+    async fn get_sportsmen(&self) -> Vec<SportsmanSummary> {
+        let perf_guard = self.performances.read().await;
+
+        perf_guard
+            .iter()
+            .map(|(sportsman, metrics)| {
+                let mut sports: Vec<&'static str> =
+                    metrics.iter().map(|r| r.metric.sport_name()).collect();
+                sports.sort_unstable();
+                sports.dedup();
+
+                SportsmanSummary {
+                    sportsman: sportsman.clone(),
+                    sports,
+                    last_recorded_at: metrics.last().map(|r| r.recorded_at),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the latest record of the sport for every sportsman who has it
+    async fn get_latest_performances(&self, sport: &str) -> Vec<(Sportsman, Record)> {
+        let perf_guard = self.performances.read().await;
+
+        perf_guard
+            .iter()
+            .filter_map(|(sportsman, metrics)| {
+                metrics
+                    .iter()
+                    .rev()
+                    .find(|r| r.metric.sport_name() == sport)
+                    .map(|r| (sportsman.clone(), r.clone()))
+            })
+            .collect()
+    }
+
+    /// Returns the whole history of the sport for every sportsman who has it
+    async fn get_histories(&self, sport: &str) -> Vec<(Sportsman, Vec<Record>)> {
+        let perf_guard = self.performances.read().await;
+
+        perf_guard
+            .iter()
+            .filter_map(|(sportsman, metrics)| {
+                let history: Vec<Record> = metrics
+                    .iter()
+                    .filter(|r| r.metric.sport_name() == sport)
+                    .cloned()
+                    .collect();
+
+                (!history.is_empty()).then(|| (sportsman.clone(), history))
+            })
+            .collect()
+    }
+
+    /// Removes the whole history of the sport
+    async fn remove_performance(&self, sportsman: Sportsman, sport: &str) -> Result<(), Error> {
         let mut perf_guard = self.performances.write().await;
 
         if let Some(existing_metrics) = perf_guard.get_mut(&sportsman) {
-            if let Some(ind) = existing_metrics
-                .iter()
-                .position(|m| m.as_any().type_id() == TypeId::of::<T>())
-            {
-                existing_metrics.swap_remove(ind);
+            let len = existing_metrics.len();
+            existing_metrics.retain(|r| r.metric.sport_name() != sport);
+
+            if existing_metrics.len() != len {
                 return Ok(());
             }
-            Err(Error::SportsmanDoesntHasMetric)
+            Err(Error::PerformanceNotFound)
         } else {
             Err(Error::SportsmanNotFound)
         }
     }
+
+    /// Adds sportsman without any records
+    async fn add_sportsman(&self, sportsman: Sportsman) -> Result<(), Error> {
+        let mut perf_guard = self.performances.write().await;
+
+        if perf_guard.contains_key(&sportsman) {
+            return Err(Error::SportsmanAlreadyExists);
+        }
+        perf_guard.insert(sportsman, Vec::new());
+
+        Ok(())
+    }
+
+    /// Returns stored sportsman with the profile
+    async fn get_sportsman(&self, sportsman: &Sportsman) -> Result<Sportsman, Error> {
+        let perf_guard = self.performances.read().await;
+
+        perf_guard
+            .get_key_value(sportsman)
+            .map(|(stored, _)| stored.clone())
+            .ok_or(Error::SportsmanNotFound)
+    }
+
+    /// Replaces the sportsman keeping all their records, name can be changed as well
+    async fn update_sportsman(
+        &self,
+        sportsman: &Sportsman,
+        updated: Sportsman,
+    ) -> Result<(), Error> {
+        let mut perf_guard = self.performances.write().await;
+
+        if *sportsman != updated && perf_guard.contains_key(&updated) {
+            return Err(Error::SportsmanAlreadyExists);
+        }
+        // key has to be removed, insert doesn't replace the key equal to the new one
+        let metrics = perf_guard
+            .remove(sportsman)
+            .ok_or(Error::SportsmanNotFound)?;
+        perf_guard.insert(updated, metrics);
+
+        Ok(())
+    }
+
+    /// Removes sportsman with all their records
+    async fn remove_sportsman(&self, sportsman: &Sportsman) -> Result<(), Error> {
+        let mut perf_guard = self.performances.write().await;
+
+        perf_guard
+            .remove(sportsman)
+            .map(|_| ())
+            .ok_or(Error::SportsmanNotFound)
+    }
 }
@@ -1,62 +1,60 @@
-use crate::models::error::Error;
+use crate::service::models::Message;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Json as AxumJson, Response};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::Value;
 use utoipa::ToSchema;
 
+/// One page of a listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Page<T = Value> {
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub items: Vec<T>,
+}
+
+impl<T: Serialize> IntoResponse for Page<T> {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, AxumJson(self)).into_response()
+    }
+}
+
+/// Messages of the successful changes, errors are reported by [`crate::models::error::Error`]
 #[derive(Serialize, Deserialize, ToSchema)]
 pub enum Responses {
     PerformanceAdded(&'static str),
     PerformanceRemoved,
-    PerformanceNotFound,
-    SportsmanNotFound,
-    InvalidPerformanceFormat(&'static str),
-    Errors(Error),
+    SportsmanCreated,
+    SportsmanUpdated,
+    SportsmanRemoved,
 }
 
 impl IntoResponse for Responses {
     fn into_response(self) -> Response {
-        let status = match self {
-            Responses::PerformanceAdded(_) => StatusCode::OK,
-            Responses::PerformanceRemoved => StatusCode::OK,
-            Responses::PerformanceNotFound => StatusCode::NOT_FOUND,
-            Responses::SportsmanNotFound => StatusCode::NOT_FOUND,
-            Responses::InvalidPerformanceFormat(_) => StatusCode::BAD_REQUEST,
-            Responses::Errors(_) => StatusCode::NOT_FOUND,
+        let (status, message) = match self {
+            Responses::PerformanceAdded(name) => (
+                StatusCode::OK,
+                Message::new(format!("{} performance added successfully", name)),
+            ),
+            Responses::PerformanceRemoved => (
+                StatusCode::OK,
+                Message::new("Performance removed successfully"),
+            ),
+            Responses::SportsmanCreated => (
+                StatusCode::CREATED,
+                Message::new("Sportsman created successfully"),
+            ),
+            Responses::SportsmanUpdated => (
+                StatusCode::OK,
+                Message::new("Sportsman updated successfully"),
+            ),
+            Responses::SportsmanRemoved => (
+                StatusCode::OK,
+                Message::new("Sportsman removed successfully"),
+            ),
         };
-        match self {
-            Responses::PerformanceAdded(name) => {
-                let json = json!({
-                    "message": format!("{} performance added successfully", name),
-                });
-                (status, AxumJson(json)).into_response()
-            }
-            Responses::PerformanceRemoved => {
-                let json = json!({
-                    "message": "Performance removed successfully",
-                });
-                (status, AxumJson(json)).into_response()
-            }
-            Responses::PerformanceNotFound => {
-                let json = json!({
-                    "message": "Performance not found",
-                });
-                (status, AxumJson(json)).into_response()
-            }
-            Responses::SportsmanNotFound => {
-                let json = json!({
-                    "message": "Sportsman not found",
-                });
-                (status, AxumJson(json)).into_response()
-            }
-            Responses::InvalidPerformanceFormat(name) => {
-                let json = json!({
-                    "message": format!("Invalid {} format", name),
-                });
-                (status, AxumJson(json)).into_response()
-            }
-            Responses::Errors(e) => e.into_response(),
-        }
+
+        (status, AxumJson(message)).into_response()
     }
 }
@@ -1,29 +1,179 @@
 use crate::models::error::Error;
+use crate::models::metrics::custom::RESERVED_SPORTSMAN_NAMES;
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Formatter;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use utoipa::ToSchema;
 
-#[derive(Debug, Hash, Eq, PartialEq)]
-pub struct Sportsman(String);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+impl Sex {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+        }
+    }
+}
+
+impl FromStr for Sex {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "male" => Ok(Sex::Male),
+            "female" => Ok(Sex::Female),
+            _ => Err(Error::InvalidProfile(format!("Unknown sex: {s}"))),
+        }
+    }
+}
+
+/// Optional demographic data of a sportsman
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Profile {
+    pub birth_date: Option<NaiveDate>,
+    pub sex: Option<Sex>,
+    /// Body mass in kg
+    pub body_mass: Option<f32>,
+    /// ISO 3166-1 alpha-3 country code, e.g. `NOR`
+    pub nationality: Option<String>,
+    pub club: Option<String>,
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(birth_date) = self.birth_date {
+            let min = NaiveDate::from_ymd_opt(1900, 1, 1).expect("valid date");
+            if birth_date < min || birth_date > Utc::now().date_naive() {
+                return Err(Error::InvalidProfile(
+                    "Birth date must be between 1900-01-01 and today".to_string(),
+                ));
+            }
+        }
+
+        if let Some(body_mass) = self.body_mass {
+            if !(body_mass > 0.0 && body_mass <= 500.0) {
+                return Err(Error::InvalidProfile(
+                    "Body mass must be in (0, 500] kg".to_string(),
+                ));
+            }
+        }
+
+        if let Some(nationality) = &self.nationality {
+            if nationality.len() != 3 || !nationality.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(Error::InvalidProfile(
+                    "Nationality must be an ISO 3166-1 alpha-3 code".to_string(),
+                ));
+            }
+        }
+
+        if let Some(club) = &self.club {
+            if club.is_empty() || club.len() > 100 {
+                return Err(Error::InvalidProfile(
+                    "Club name must be from 1 to 100 bytes long".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Sportsmen are identified by name, so a sportsman built by [`Sportsman::new`]
+/// can be used to look up the stored one with all profile data
+#[derive(Debug, Clone)]
+pub struct Sportsman {
+    id: Option<i32>,
+    name: String,
+    profile: Profile,
+}
 
 impl Sportsman {
     pub fn new(name: String) -> Result<Self, Error> {
         if name.len() > 50 {
             return Err(Error::NameTooLong);
         }
-        Ok(Self(name))
+        if name.trim().is_empty() {
+            return Err(Error::InvalidName("must not be blank".to_string()));
+        }
+        if RESERVED_SPORTSMAN_NAMES.contains(&name.as_str()) {
+            return Err(Error::InvalidName(format!("{name:?} is reserved")));
+        }
+        Ok(Self::unchecked_new(name))
     }
 
     pub fn unchecked_new(name: String) -> Self {
-        Self(name)
+        Self {
+            id: None,
+            name,
+            profile: Profile::default(),
+        }
     }
+
+    pub fn with_id(mut self, id: i32) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn with_profile(mut self, profile: Profile) -> Result<Self, Error> {
+        profile.validate()?;
+        self.profile = profile;
+        Ok(self)
+    }
+
+    pub fn unchecked_with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn id(&self) -> Option<i32> {
+        self.id
+    }
+
     pub fn name(&self) -> String {
-        self.0.clone()
+        self.name.clone()
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Full years at the given date
+    pub fn age(&self, at: NaiveDate) -> Option<u32> {
+        let birth_date = self.profile.birth_date?;
+        let mut age = at.year() - birth_date.year();
+        if (at.month(), at.day()) < (birth_date.month(), birth_date.day()) {
+            age -= 1;
+        }
+        u32::try_from(age).ok()
+    }
+}
+
+impl PartialEq for Sportsman {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Sportsman {}
+
+impl Hash for Sportsman {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
     }
 }
 
 impl fmt::Display for Sportsman {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Name: {}", self.0)?;
+        writeln!(f, "Name: {}", self.name)?;
         Ok(())
     }
 }
@@ -0,0 +1,59 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Club the sportsmen belong to, their performances belong to the same tenant.
+/// Names of the sportsmen are unique within a tenant only
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Tenant(String);
+
+impl Tenant {
+    pub const DEFAULT: &'static str = "default";
+
+    /// Ids are lowercase ascii letters, digits, `-` and `_`, at most 50 bytes long
+    pub fn new(id: String) -> Result<Self, String> {
+        if id.is_empty() || id.len() > 50 {
+            return Err(format!("tenant {id:?} must be from 1 to 50 bytes long"));
+        }
+        let is_valid =
+            |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_';
+        if !id.chars().all(is_valid) {
+            return Err(format!(
+                "tenant {id:?} may only have lowercase letters, digits, - and _"
+            ));
+        }
+
+        Ok(Self(id))
+    }
+
+    /// Tenant read from the storage
+    pub fn unchecked_new(id: String) -> Self {
+        Self(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Tenant of the data stored before tenants were introduced
+impl Default for Tenant {
+    fn default() -> Self {
+        Self(Self::DEFAULT.to_string())
+    }
+}
+
+impl TryFrom<String> for Tenant {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::new(id)
+    }
+}
+
+impl fmt::Display for Tenant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
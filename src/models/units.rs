@@ -0,0 +1,289 @@
+use crate::models::metrics::widen;
+use crate::models::validation::FieldError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Units values can be sent in and shown in, named by their symbols
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Meter,
+    Kilometer,
+    Foot,
+    Yard,
+    Mile,
+    MetersPerSecond,
+    KilometersPerHour,
+    FeetPerSecond,
+    MilesPerHour,
+    Kilogram,
+    Pound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Length,
+    Speed,
+    Mass,
+}
+
+impl Unit {
+    const ALL: [Unit; 11] = [
+        Unit::Meter,
+        Unit::Kilometer,
+        Unit::Foot,
+        Unit::Yard,
+        Unit::Mile,
+        Unit::MetersPerSecond,
+        Unit::KilometersPerHour,
+        Unit::FeetPerSecond,
+        Unit::MilesPerHour,
+        Unit::Kilogram,
+        Unit::Pound,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Meter => "m",
+            Unit::Kilometer => "km",
+            Unit::Foot => "ft",
+            Unit::Yard => "yd",
+            Unit::Mile => "mi",
+            Unit::MetersPerSecond => "m/s",
+            Unit::KilometersPerHour => "km/h",
+            Unit::FeetPerSecond => "ft/s",
+            Unit::MilesPerHour => "mph",
+            Unit::Kilogram => "kg",
+            Unit::Pound => "lb",
+        }
+    }
+
+    pub fn dimension(self) -> Dimension {
+        match self {
+            Unit::Meter | Unit::Kilometer | Unit::Foot | Unit::Yard | Unit::Mile => {
+                Dimension::Length
+            }
+            Unit::MetersPerSecond
+            | Unit::KilometersPerHour
+            | Unit::FeetPerSecond
+            | Unit::MilesPerHour => Dimension::Speed,
+            Unit::Kilogram | Unit::Pound => Dimension::Mass,
+        }
+    }
+
+    /// Size of the unit in meters, meters per second or kilograms
+    fn si_factor(self) -> f64 {
+        match self {
+            Unit::Meter => 1.0,
+            Unit::Kilometer => 1000.0,
+            Unit::Foot => 0.3048,
+            Unit::Yard => 0.9144,
+            Unit::Mile => 1609.344,
+            Unit::MetersPerSecond => 1.0,
+            Unit::KilometersPerHour => 1000.0 / 3600.0,
+            Unit::FeetPerSecond => 0.3048,
+            Unit::MilesPerHour => 1609.344 / 3600.0,
+            Unit::Kilogram => 1.0,
+            Unit::Pound => 0.453_592_37,
+        }
+    }
+
+    /// None if the units measure different things
+    pub fn convert(self, value: f32, to: Unit) -> Option<f32> {
+        if self == to {
+            return Some(value);
+        }
+        (self.dimension() == to.dimension())
+            .then(|| (value as f64 * self.si_factor() / to.si_factor()) as f32)
+    }
+
+    /// Unit of the same scale in the other system, e.g. `mi` for `km`
+    pub const fn counterpart(self, system: UnitSystem) -> Unit {
+        match (system, self) {
+            (UnitSystem::Metric, Unit::Foot | Unit::Yard) => Unit::Meter,
+            (UnitSystem::Metric, Unit::Mile) => Unit::Kilometer,
+            (UnitSystem::Metric, Unit::FeetPerSecond) => Unit::MetersPerSecond,
+            (UnitSystem::Metric, Unit::MilesPerHour) => Unit::KilometersPerHour,
+            (UnitSystem::Metric, Unit::Pound) => Unit::Kilogram,
+            (UnitSystem::Imperial, Unit::Meter) => Unit::Foot,
+            (UnitSystem::Imperial, Unit::Kilometer) => Unit::Mile,
+            (UnitSystem::Imperial, Unit::MetersPerSecond) => Unit::FeetPerSecond,
+            (UnitSystem::Imperial, Unit::KilometersPerHour) => Unit::MilesPerHour,
+            (UnitSystem::Imperial, Unit::Kilogram) => Unit::Pound,
+            (_, unit) => unit,
+        }
+    }
+
+    /// Units values of the dimension can be sent in
+    pub fn of_dimension(dimension: Dimension) -> impl Iterator<Item = Unit> {
+        Self::ALL
+            .into_iter()
+            .filter(move |unit| unit.dimension() == dimension)
+    }
+
+    fn symbols_of(dimension: Dimension) -> String {
+        Self::of_dimension(dimension)
+            .map(Unit::symbol)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|unit| unit.symbol() == s)
+            .ok_or_else(|| format!("Unknown unit {s}"))
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// System of units the values are shown in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    /// km, km/h, m, kg
+    #[default]
+    Metric,
+    /// mi, mph, yd or ft, lb
+    Imperial,
+}
+
+impl FromStr for UnitSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "metric" => Ok(UnitSystem::Metric),
+            "imperial" => Ok(UnitSystem::Imperial),
+            _ => Err(format!(
+                "Unknown units {s}, expected one of: metric, imperial"
+            )),
+        }
+    }
+}
+
+impl Display for UnitSystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnitSystem::Metric => write!(f, "metric"),
+            UnitSystem::Imperial => write!(f, "imperial"),
+        }
+    }
+}
+
+/// Unit the value of a field is stored in and the units it is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Units {
+    pub stored: Unit,
+    pub metric: Unit,
+    pub imperial: Unit,
+}
+
+impl Units {
+    pub const fn of(stored: Unit) -> Self {
+        Self {
+            stored,
+            metric: stored.counterpart(UnitSystem::Metric),
+            imperial: stored.counterpart(UnitSystem::Imperial),
+        }
+    }
+
+    /// Overrides the default counterpart, e.g. pools are measured in yards rather than feet
+    pub const fn imperial(self, imperial: Unit) -> Self {
+        Self { imperial, ..self }
+    }
+
+    pub fn shown_in(&self, system: UnitSystem) -> Unit {
+        match system {
+            UnitSystem::Metric => self.metric,
+            UnitSystem::Imperial => self.imperial,
+        }
+    }
+
+    /// Converts the stored value for the system
+    pub fn show(&self, value: f32, system: UnitSystem) -> f32 {
+        self.stored
+            .convert(value, self.shown_in(system))
+            .unwrap_or(value)
+    }
+
+    /// Plain number is taken in the stored unit,
+    /// `{"value": 3.1, "unit": "mi"}` is converted to it
+    fn read(&self, field: &str, value: &Value) -> Result<Option<f32>, FieldError> {
+        let Value::Object(object) = value else {
+            return Ok(None);
+        };
+
+        let number = object
+            .get("value")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| FieldError::new(format!("{field}.value"), "must be a number"))?;
+        let expected = || {
+            let symbols = Unit::symbols_of(self.stored.dimension());
+            FieldError::new(
+                format!("{field}.unit"),
+                format!("must be one of: {symbols}"),
+            )
+        };
+        let unit: Unit = object
+            .get("unit")
+            .and_then(Value::as_str)
+            .ok_or_else(expected)?
+            .parse()
+            .map_err(|_| expected())?;
+        if let Some(unknown) = object.keys().find(|key| *key != "value" && *key != "unit") {
+            return Err(FieldError::new(format!("{field}.{unknown}"), "is unknown"));
+        }
+
+        unit.convert(number as f32, self.stored)
+            .map(Some)
+            .ok_or_else(expected)
+    }
+}
+
+/// Replaces values sent with units by numbers in the stored units,
+/// returns errors of the fields that couldn't be converted
+pub fn read_units(
+    units: &[(&'static str, Units)],
+    object: &mut Map<String, Value>,
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for (field, field_units) in units {
+        let Some(value) = object.get_mut(*field) else {
+            continue;
+        };
+        match field_units.read(field, value) {
+            Ok(Some(converted)) => *value = json!(converted),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+
+    errors
+}
+
+/// Converts stored numbers for the system, other fields are kept as is
+pub fn show_units(
+    units: &[(&'static str, Units)],
+    object: &mut Map<String, Value>,
+    system: UnitSystem,
+) {
+    for (field, field_units) in units {
+        if let Some(value) = object.get_mut(*field) {
+            if let Some(number) = value.as_f64() {
+                *value = json!(widen(field_units.show(number as f32, system)));
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use utoipa::ToSchema;
+
+/// Invalid field of the request and the reason, e.g. `speed` `must be positive`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.reason)
+    }
+}
+
+/// Collects errors of all checked fields instead of stopping at the first one
+#[derive(Debug, Default)]
+pub struct Checks(Vec<FieldError>);
+
+impl Checks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn positive(self, field: &str, value: f32) -> Self {
+        self.check(field, value, value > 0.0, "must be positive")
+    }
+
+    pub fn non_negative(self, field: &str, value: f32) -> Self {
+        self.check(field, value, value >= 0.0, "must not be negative")
+    }
+
+    /// Bounds are inclusive
+    pub fn between(self, field: &str, value: f32, min: f32, max: f32) -> Self {
+        let reason = format!("must be between {min} and {max}");
+        self.check(field, value, (min..=max).contains(&value), &reason)
+    }
+
+    pub fn error(mut self, field: &str, reason: &str) -> Self {
+        self.0.push(FieldError::new(field, reason));
+        self
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+
+    /// Values too big for `f32` are parsed as infinity, so finiteness is checked first
+    fn check(self, field: &str, value: f32, valid: bool, reason: &str) -> Self {
+        if !value.is_finite() {
+            self.error(field, "must be a finite number")
+        } else if !valid {
+            self.error(field, reason)
+        } else {
+            self
+        }
+    }
+}
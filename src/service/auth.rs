@@ -0,0 +1,370 @@
+use crate::models::error::Error;
+use crate::models::tenant::Tenant;
+use crate::service::config::{AuthConfig, StartupError};
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Shortest `JWT_SECRET` accepted, HS256 keys shouldn't be shorter than the hash
+const MIN_SECRET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Reads own profile and performances
+    Athlete,
+    /// Reads everything, changes data of the squad
+    Coach,
+    Admin,
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Athlete => write!(f, "athlete"),
+            Role::Coach => write!(f, "coach"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// What the caller is allowed to do, shared by the API keys and the token claims
+#[derive(Debug, Clone, Deserialize)]
+struct Grant {
+    role: Role,
+    /// Club the caller belongs to, data of other tenants isn't visible
+    #[serde(default)]
+    tenant: Tenant,
+    /// Sportsman the athlete is
+    #[serde(default)]
+    sportsman: Option<String>,
+    /// Sportsmen the coach is responsible for
+    #[serde(default)]
+    squad: Vec<String>,
+}
+
+/// Authenticated client of the request, taken by the handlers to check [`Access`]
+#[derive(Debug, Clone)]
+pub struct Caller {
+    /// Name of the API key or subject of the token
+    pub name: String,
+    pub role: Role,
+    pub tenant: Tenant,
+    pub sportsman: Option<String>,
+    pub squad: Vec<String>,
+}
+
+impl Caller {
+    fn new(name: String, grant: Grant) -> Result<Self, String> {
+        if grant.role == Role::Athlete && grant.sportsman.is_none() {
+            return Err(format!("athlete {name} must have a sportsman"));
+        }
+
+        Ok(Self {
+            name,
+            role: grant.role,
+            tenant: grant.tenant,
+            sportsman: grant.sportsman,
+            squad: grant.squad,
+        })
+    }
+
+    /// Used when `AUTH_DISABLED` is set, data of the default tenant is served
+    fn anonymous() -> Self {
+        Self {
+            name: "anonymous".to_string(),
+            role: Role::Admin,
+            tenant: Tenant::default(),
+            sportsman: None,
+            squad: Vec::new(),
+        }
+    }
+
+    pub fn authorize(&self, access: Access) -> Result<(), Error> {
+        let allowed = match (self.role, access) {
+            (Role::Admin, _) => true,
+            (Role::Coach, Access::ReadAll | Access::Read(_)) => true,
+            (Role::Coach, Access::Write(name)) => self.squad.iter().any(|member| member == name),
+            (Role::Athlete, Access::Read(name)) => self.sportsman.as_deref() == Some(name),
+            _ => false,
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::Forbidden(format!(
+                "{} {} can't {access}",
+                self.role, self.name
+            )))
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Caller
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Caller>()
+            .cloned()
+            .ok_or_else(|| Error::Unauthenticated("Route isn't authenticated".to_string()))
+    }
+}
+
+/// Access to the data of the sportsmen checked by [`Caller::authorize`]
+#[derive(Debug, Clone, Copy)]
+pub enum Access<'a> {
+    /// Listings and leaderboards
+    ReadAll,
+    Read(&'a str),
+    /// Adding and removing performances, creating and updating the sportsman
+    Write(&'a str),
+    /// Removing the sportsman with all the performances
+    Remove(&'a str),
+}
+
+impl Display for Access<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Access::ReadAll => write!(f, "read data of all sportsmen"),
+            Access::Read(name) => write!(f, "read data of {name}"),
+            Access::Write(name) => write!(f, "change data of {name}"),
+            Access::Remove(name) => write!(f, "remove {name}"),
+        }
+    }
+}
+
+/// File set by `AUTH_KEYS_FILE`
+#[derive(Deserialize)]
+struct KeysFile {
+    keys: Vec<KeyEntry>,
+}
+
+#[derive(Deserialize)]
+struct KeyEntry {
+    name: String,
+    /// Hex encoded SHA-256 of the key, the key itself isn't stored
+    key_sha256: String,
+    #[serde(flatten)]
+    grant: Grant,
+}
+
+#[derive(Deserialize)]
+struct TokenHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    /// Expiration as unix timestamp, tokens without it aren't accepted
+    exp: i64,
+    #[serde(flatten)]
+    grant: Grant,
+}
+
+/// API keys and the secret of HS256 tokens, both are verified locally
+pub struct Auth {
+    /// Callers by SHA-256 of their keys
+    keys: HashMap<String, Caller>,
+    jwt_secret: Option<Vec<u8>>,
+    disabled: bool,
+}
+
+impl Auth {
+    pub fn load(config: &AuthConfig) -> Result<Self, StartupError> {
+        if config.disabled {
+            log::warn!("Authentication is disabled, every request is served as admin");
+            return Ok(Self {
+                keys: HashMap::new(),
+                jwt_secret: None,
+                disabled: true,
+            });
+        }
+
+        if config.keys_file.is_none() && config.jwt_secret.is_none() {
+            return Err(StartupError::MissingVar(
+                "AUTH_KEYS_FILE, JWT_SECRET or AUTH_DISABLED",
+            ));
+        }
+        if let Some(secret) = &config.jwt_secret {
+            if secret.len() < MIN_SECRET_LEN {
+                return Err(StartupError::InvalidVar {
+                    name: "JWT_SECRET",
+                    value: "<hidden>".to_string(),
+                    reason: format!("must be at least {MIN_SECRET_LEN} bytes"),
+                });
+            }
+        }
+
+        let keys = match &config.keys_file {
+            Some(path) => load_keys(path)?,
+            None => HashMap::new(),
+        };
+
+        Ok(Self {
+            keys,
+            jwt_secret: config.jwt_secret.as_ref().map(|s| s.as_bytes().to_vec()),
+            disabled: false,
+        })
+    }
+
+    /// `Authorization: Bearer <token>` or `X-API-Key: <key>`
+    fn caller(&self, headers: &HeaderMap) -> Result<Caller, Error> {
+        if self.disabled {
+            return Ok(Caller::anonymous());
+        }
+
+        if let Some(value) = headers.get(AUTHORIZATION) {
+            let token = value
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or_else(|| {
+                    Error::Unauthenticated(
+                        "Unsupported authorization scheme, expected Bearer".to_string(),
+                    )
+                })?;
+            return self.verify_token(token.trim());
+        }
+
+        if let Some(value) = headers.get(API_KEY_HEADER) {
+            let digest = format!("{:x}", Sha256::digest(value.as_bytes()));
+            return self
+                .keys
+                .get(&digest)
+                .cloned()
+                .ok_or_else(|| Error::Unauthenticated("Unknown API key".to_string()));
+        }
+
+        Err(Error::Unauthenticated(
+            "Missing X-API-Key header or bearer token".to_string(),
+        ))
+    }
+
+    fn verify_token(&self, token: &str) -> Result<Caller, Error> {
+        let invalid = |reason: &str| Error::Unauthenticated(format!("Invalid token: {reason}"));
+        let Some(secret) = &self.jwt_secret else {
+            return Err(Error::Unauthenticated(
+                "Bearer tokens aren't accepted, use X-API-Key header".to_string(),
+            ));
+        };
+
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("expected header.payload.signature"));
+        };
+
+        let alg = decode_part::<TokenHeader>(header)
+            .map_err(|e| invalid(&e))?
+            .alg;
+        if alg != "HS256" {
+            return Err(invalid(&format!(
+                "unsupported algorithm {alg}, expected HS256"
+            )));
+        }
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| invalid("signature isn't base64url"))?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+        mac.update(header.as_bytes());
+        mac.update(b".");
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| invalid("signature doesn't match"))?;
+
+        let claims = decode_part::<Claims>(payload).map_err(|e| invalid(&e))?;
+        if claims.exp <= Utc::now().timestamp() {
+            return Err(Error::Unauthenticated("Token expired".to_string()));
+        }
+
+        Caller::new(claims.sub, claims.grant).map_err(|e| invalid(&e))
+    }
+}
+
+/// Keeps the caller in the request extensions, requests without valid credentials
+/// are answered with 401. Access to the routes is checked by the handlers
+pub async fn authenticate(
+    State(auth): State<Arc<Auth>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match auth.caller(request.headers()) {
+        Ok(caller) => {
+            request.extensions_mut().insert(caller);
+            next.run(request).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+fn load_keys(path: &Path) -> Result<HashMap<String, Caller>, StartupError> {
+    let invalid = |reason: String| StartupError::InvalidAuthKeys {
+        path: path.display().to_string(),
+        reason,
+    };
+
+    let content = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+    let file: KeysFile = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| invalid(e.to_string()))?,
+        Some("json") => serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?,
+        _ => return Err(invalid("expected .toml or .json file".to_string())),
+    };
+
+    let mut keys = HashMap::new();
+    for entry in file.keys {
+        let digest = entry.key_sha256.to_ascii_lowercase();
+        if digest.len() != 64 || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid(format!(
+                "key_sha256 of {} must be 64 hex digits",
+                entry.name
+            )));
+        }
+
+        let caller = Caller::new(entry.name, entry.grant).map_err(invalid)?;
+        log::info!(
+            "API key {} of {} in {} is accepted",
+            caller.name,
+            caller.role,
+            caller.tenant
+        );
+        if let Some(previous) = keys.insert(digest, caller) {
+            return Err(invalid(format!("key of {} is used twice", previous.name)));
+        }
+    }
+
+    Ok(keys)
+}
+
+fn decode_part<T: DeserializeOwned>(part: &str) -> Result<T, String> {
+    let json = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|_| "part isn't base64url".to_string())?;
+
+    serde_json::from_slice(&json).map_err(|e| e.to_string())
+}
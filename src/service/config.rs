@@ -0,0 +1,254 @@
+use crate::models::error::Error;
+use crate::service::core::{Storage, Url};
+use sqlx::migrate::MigrateError;
+use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use sqlx::sqlite::SqliteConnectOptions;
+use std::env;
+use std::error::Error as StdError;
+use std::fmt::{Debug, Display, Formatter};
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Errors that prevent the service from starting
+#[derive(Debug)]
+pub enum StartupError {
+    MissingVar(&'static str),
+    InvalidVar {
+        name: &'static str,
+        value: String,
+        reason: String,
+    },
+    InvalidSportsSchema {
+        path: String,
+        reason: String,
+    },
+    InvalidAuthKeys {
+        path: String,
+        reason: String,
+    },
+    Connection(sqlx::Error),
+    Migration(MigrateError),
+    CreateStorage(Error),
+    LoadTracker(Error),
+    Bind {
+        url: String,
+        source: io::Error,
+    },
+    Serve(io::Error),
+}
+
+impl Display for StartupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupError::MissingVar(name) => {
+                write!(f, "Environment variable {name} is not set")
+            }
+            StartupError::InvalidVar {
+                name,
+                value,
+                reason,
+            } => write!(f, "Invalid value {value:?} of {name}: {reason}"),
+            StartupError::InvalidSportsSchema { path, reason } => {
+                write!(f, "Invalid sports schema {path}: {reason}")
+            }
+            StartupError::InvalidAuthKeys { path, reason } => {
+                write!(f, "Invalid API keys {path}: {reason}")
+            }
+            StartupError::Connection(e) => write!(f, "Couldn't connect to the database: {e}"),
+            StartupError::Migration(e) => write!(f, "Couldn't apply migrations: {e}"),
+            StartupError::CreateStorage(e) => {
+                write!(f, "Couldn't create storage of the sports from schema: {e}")
+            }
+            StartupError::LoadTracker(e) => write!(f, "Couldn't load stored performances: {e}"),
+            StartupError::Bind { url, source } => write!(f, "Couldn't bind {url}: {source}"),
+            StartupError::Serve(e) => write!(f, "Couldn't serve requests: {e}"),
+        }
+    }
+}
+
+impl StdError for StartupError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            StartupError::MissingVar(_)
+            | StartupError::InvalidVar { .. }
+            | StartupError::InvalidSportsSchema { .. }
+            | StartupError::InvalidAuthKeys { .. } => None,
+            StartupError::Connection(e) => Some(e),
+            StartupError::CreateStorage(e) | StartupError::LoadTracker(e) => Some(e),
+            StartupError::Migration(e) => Some(e),
+            StartupError::Bind { source, .. } => Some(source),
+            StartupError::Serve(e) => Some(e),
+        }
+    }
+}
+
+/// How many times and how often to retry an operation failing on startup,
+/// delay is doubled after every attempt up to `max_backoff`
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub async fn retry<T, E, F, Fut>(&self, what: &str, mut operation: F) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= self.attempts => return Err(e),
+                Err(e) => {
+                    log::warn!(
+                        "{what} failed ({attempt}/{}): {e}\n Retrying in {backoff:?}...",
+                        self.attempts
+                    );
+                }
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.max_backoff);
+            attempt += 1;
+        }
+    }
+}
+
+/// Settings shared by the database backed storages
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// `DB_MAX_CONNECTIONS`, SQLite always uses a single connection
+    pub max_connections: u32,
+    /// `DB_CONNECT_TIMEOUT_SECS`
+    pub connect_timeout: Duration,
+    /// `DB_CONNECT_ATTEMPTS` and `DB_RETRY_BACKOFF_MS`
+    pub retry: RetryPolicy,
+}
+
+/// Credentials the callers are authenticated with, at least one kind is required
+/// unless authentication is disabled
+#[derive(Clone, Default)]
+pub struct AuthConfig {
+    /// `AUTH_KEYS_FILE`, `.toml` or `.json` file with the API keys and their roles
+    pub keys_file: Option<PathBuf>,
+    /// `JWT_SECRET`, HS256 secret of the bearer tokens
+    pub jwt_secret: Option<String>,
+    /// `AUTH_DISABLED`, every request is served as admin
+    pub disabled: bool,
+}
+
+impl Debug for AuthConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("keys_file", &self.keys_file)
+            .field("jwt_secret", &self.jwt_secret.as_ref().map(|_| "<hidden>"))
+            .field("disabled", &self.disabled)
+            .finish()
+    }
+}
+
+/// Service configuration read from environment (and `.env` file)
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// `STORAGE`: postgres (default), sqlite or memory
+    pub storage: Storage,
+    /// `SERVICE_URL`
+    pub service_url: Url,
+    /// `SHUTDOWN_TIMEOUT_SECS`, how long in-flight requests are waited for on shutdown
+    pub shutdown_timeout: Duration,
+    /// `SPORTS_SCHEMA`, `.toml` or `.json` file with sports defined at runtime
+    pub sports_schema: Option<PathBuf>,
+    pub auth: AuthConfig,
+    pub pool: PoolConfig,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, StartupError> {
+        let pool = PoolConfig {
+            max_connections: var_or("DB_MAX_CONNECTIONS", 10)?,
+            connect_timeout: Duration::from_secs(var_or("DB_CONNECT_TIMEOUT_SECS", 5)?),
+            retry: RetryPolicy {
+                attempts: var_or("DB_CONNECT_ATTEMPTS", 5)?,
+                initial_backoff: Duration::from_millis(var_or("DB_RETRY_BACKOFF_MS", 500)?),
+                max_backoff: Duration::from_secs(10),
+            },
+        };
+        if pool.max_connections == 0 {
+            return Err(invalid("DB_MAX_CONNECTIONS", "0", "must be positive"));
+        }
+        if pool.retry.attempts == 0 {
+            return Err(invalid("DB_CONNECT_ATTEMPTS", "0", "must be positive"));
+        }
+
+        Ok(Self {
+            storage: var_or("STORAGE", Storage::Postgres)?,
+            service_url: Url(var("SERVICE_URL")?),
+            shutdown_timeout: Duration::from_secs(var_or("SHUTDOWN_TIMEOUT_SECS", 20)?),
+            sports_schema: env::var_os("SPORTS_SCHEMA").map(PathBuf::from),
+            auth: AuthConfig {
+                keys_file: env::var_os("AUTH_KEYS_FILE").map(PathBuf::from),
+                jwt_secret: env::var("JWT_SECRET").ok(),
+                disabled: var_or("AUTH_DISABLED", false)?,
+            },
+            pool,
+        })
+    }
+}
+
+/// `DATABASE_URL` if set, otherwise built from `POSTGRES_HOST` (default `pg`), `POSTGRES_PORT`,
+/// `POSTGRES_USER`, `POSTGRES_PASSWORD`, `POSTGRES_DB` and `POSTGRES_SSLMODE`
+pub fn postgres_connect_options() -> Result<PgConnectOptions, StartupError> {
+    if let Ok(url) = env::var("DATABASE_URL") {
+        return PgConnectOptions::from_str(&url)
+            .map_err(|e| invalid("DATABASE_URL", "<hidden>", &e.to_string()));
+    }
+
+    let ssl_mode: PgSslMode = var_or("POSTGRES_SSLMODE", PgSslMode::Prefer)?;
+
+    Ok(PgConnectOptions::new()
+        .host(&env::var("POSTGRES_HOST").unwrap_or_else(|_| "pg".to_string()))
+        .port(var_or("POSTGRES_PORT", 5432)?)
+        .username(&var("POSTGRES_USER")?)
+        .password(&var("POSTGRES_PASSWORD")?)
+        .database(&var("POSTGRES_DB")?)
+        .ssl_mode(ssl_mode))
+}
+
+/// `SQLITE_URL`, `sqlite://athletes.db` by default
+pub fn sqlite_connect_options() -> Result<SqliteConnectOptions, StartupError> {
+    let url = env::var("SQLITE_URL").unwrap_or_else(|_| "sqlite://athletes.db".to_string());
+
+    SqliteConnectOptions::from_str(&url).map_err(|e| invalid("SQLITE_URL", &url, &e.to_string()))
+}
+
+fn var(name: &'static str) -> Result<String, StartupError> {
+    env::var(name).map_err(|_| StartupError::MissingVar(name))
+}
+
+fn var_or<T>(name: &'static str, default: T) -> Result<T, StartupError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|e: T::Err| invalid(name, &value, &e.to_string())),
+        Err(_) => Ok(default),
+    }
+}
+
+fn invalid(name: &'static str, value: &str, reason: &str) -> StartupError {
+    StartupError::InvalidVar {
+        name,
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
@@ -1,40 +1,79 @@
-use crate::models::metrics::biathlon::Biathlon;
-use crate::models::metrics::running::Running;
-use crate::models::metrics::weight_lifting::WeightLifting;
-use crate::models::performance_tracker::PerformanceTracker;
+use crate::models::audit::{AuditEntry, Operation};
+use crate::models::performance_tracker::{PerformanceTracker, Record};
 use crate::models::responses::Responses;
 use crate::models::sportsman::Sportsman;
-use crate::traits::traits::{Metric, Pool, SportPerformance};
+use crate::traits::traits::{Pool, SportPerformance};
 use axum::extract::Path;
 use axum::response::IntoResponse;
-use std::env;
 
-use crate::models::error::Error;
-use crate::service::models::{BiathlonPerformance, RunningPerformance, WeightLiftingPerformance};
-use crate::service::postgres::postgres_pool::DBPool;
-use axum::http::StatusCode;
-use axum::routing::{delete, get, post};
+use crate::models::error::{Error, Problem};
+use crate::models::leaderboard::Leaderboard;
+use crate::models::responses::Page;
+use crate::models::validation::FieldError;
+use crate::service::auth::{authenticate, Access, Auth, Caller};
+use crate::service::config::{Config, StartupError};
+use crate::service::extract::{ApiJson, ApiQuery, CsvBody, OutputUnits};
+use crate::service::import;
+use crate::service::middleware::request_id;
+use crate::service::models::{
+    AuditQuery, AuditRecord, ImportReport, LatestPerformance, LeaderboardQuery, ListItem,
+    ListQuery, Liveness, Message, NewSportsman, Ranking, Readiness, ReadinessChecks, Recorded,
+    RejectedRow, SportsmanOverview, SportsmanProfile, SportsmanUpdate, UnitsQuery,
+};
+use crate::service::openapi::{
+    document_security, document_sports, Performance, PerformanceRequest,
+};
+use crate::service::registry::SportRegistry;
+use axum::http::{Method, StatusCode, Uri};
+use axum::routing::{delete, get, patch, post};
 use axum::{Extension, Json, Router};
+use chrono::{SubsecRound, Utc};
 use serde_json::json;
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::future::IntoFuture;
+use std::io;
+use std::net::SocketAddr;
+use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
+use tokio::sync::Notify;
 
+use utoipa::openapi;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        health_live,
+        health_ready,
+        get_sportsmen,
+        get_sportsman_profile,
+        get_performances_by_sport,
+        get_leaderboard_by_sport,
         get_performance_by_sport,
+        get_history_by_sport,
         add_performance_by_sport,
         remove_performance_by_sport,
+        import_performances,
+        create_sportsman,
+        update_sportsman,
+        remove_sportsman,
+        get_audit,
     ),
-    components()
+    components(schemas(Problem, FieldError)),
+    tags(
+        (name = "health", description = "Состояние сервиса / Service health"),
+        (name = "sportsmen", description = "Спортсмены и их профили / Sportsmen and their profiles"),
+        (name = "performances", description = "Результаты по видам спорта / Performances by sport"),
+        (name = "audit", description = "Журнал изменений результатов / Audit log of performance changes")
+    )
 )]
 struct ApiDoc;
 
+#[derive(Debug, Clone)]
 pub struct Url(pub String);
 
 impl Display for Url {
@@ -43,30 +82,70 @@ impl Display for Url {
     }
 }
 
-pub struct Service {
+/// Storage backend selected by `STORAGE` variable
+#[derive(Debug, Clone, Copy)]
+pub enum Storage {
+    Postgres,
+    Sqlite,
+    Memory,
+}
+
+impl FromStr for Storage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "postgres" => Ok(Storage::Postgres),
+            "sqlite" => Ok(Storage::Sqlite),
+            "memory" => Ok(Storage::Memory),
+            _ => Err(format!(
+                "Unknown storage {s}, expected one of: postgres, sqlite, memory"
+            )),
+        }
+    }
+}
+
+impl Display for Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Storage::Postgres => write!(f, "postgres"),
+            Storage::Sqlite => write!(f, "sqlite"),
+            Storage::Memory => write!(f, "memory"),
+        }
+    }
+}
+
+pub struct Service<S: Pool> {
     router: Router,
     tcp_listener: TcpListener,
-    tracker: Arc<PerformanceTracker>,
-    pool: Arc<DBPool>,
+    pool: Arc<S>,
+    shutdown_timeout: Duration,
 }
 
-impl Service {
-    pub async fn new() -> Self {
-        let pool = Arc::new(DBPool::new().await);
+impl<S: Pool> Service<S> {
+    pub async fn new(
+        pool: S,
+        registry: SportRegistry,
+        auth: Auth,
+        config: &Config,
+    ) -> Result<Self, StartupError> {
+        let started_at = Instant::now();
+        let pool = Arc::new(pool);
+
+        registry.create_storages(pool.as_ref()).await?;
+        let registry = Arc::new(registry);
 
         let tracker = Arc::new(
-            pool.get_performance_tracker()
+            pool.get_performance_tracker(&registry)
                 .await
-                .expect("Couldn't load tracker"),
+                .map_err(StartupError::LoadTracker)?,
         );
+        tracker.mark_loaded();
 
-        let url = Url(env::var("SERVICE_URL").expect("SERVICE_URL not found in .env file"));
-        let tcp_listener = retry_to_bind(&url)
-            .await
-            .expect("Error in binding tcp_listener");
+        let tcp_listener = retry_to_bind(&config.service_url).await?;
 
-        let router = Router::new()
-            .merge(SwaggerUi::new("/swagger").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        // health checks and docs stay open
+        let api = Router::new()
             .merge(routes_get_performance(Arc::clone(&tracker)))
             .merge(routes_add_performance(
                 Arc::clone(&tracker),
@@ -75,24 +154,122 @@ impl Service {
             .merge(routes_remove_performance(
                 Arc::clone(&tracker),
                 Arc::clone(&pool),
+            ))
+            .merge(routes_import(Arc::clone(&tracker), Arc::clone(&pool)))
+            .merge(routes_sportsmen(Arc::clone(&tracker), Arc::clone(&pool)))
+            .merge(routes_audit(Arc::clone(&pool)))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(auth),
+                authenticate,
             ));
 
-        Self {
+        let router = Router::new()
+            .merge(SwaggerUi::new("/swagger").url("/api-docs/openapi.json", api_doc(&registry)))
+            .merge(routes_health(
+                Arc::clone(&tracker),
+                Arc::clone(&pool),
+                started_at,
+            ))
+            .merge(api)
+            .fallback(route_not_found)
+            .method_not_allowed_fallback(method_not_allowed)
+            .layer(Extension(registry))
+            .layer(axum::middleware::from_fn(request_id));
+
+        Ok(Self {
             router,
             tcp_listener,
-            tracker,
             pool,
-        }
+            shutdown_timeout: config.shutdown_timeout,
+        })
+    }
+
+    /// Bound address, e.g. to find the port chosen for `SERVICE_URL` with port 0
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.tcp_listener.local_addr()
     }
 
-    pub async fn start(self) {
-        axum::serve(self.tcp_listener, self.router)
+    /// Serves requests until SIGINT or SIGTERM, then stops accepting connections
+    /// and waits for in-flight requests at most `shutdown_timeout` before closing the pool
+    pub async fn start(self) -> Result<(), StartupError> {
+        let shutdown = Arc::new(Notify::new());
+
+        let signal = Arc::clone(&shutdown);
+        let server = axum::serve(self.tcp_listener, self.router)
+            .with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                log::info!("Shutting down, waiting for in-flight requests...");
+                signal.notify_one();
+            })
+            .into_future();
+
+        let drain_timeout = async {
+            shutdown.notified().await;
+            tokio::time::sleep(self.shutdown_timeout).await;
+        };
+
+        tokio::select! {
+            res = server => res.map_err(StartupError::Serve)?,
+            _ = drain_timeout => log::warn!(
+                "Requests weren't finished in {:?}, dropping them",
+                self.shutdown_timeout
+            ),
+        }
+
+        // connections still held by dropped requests would block closing forever
+        if tokio::time::timeout(self.shutdown_timeout, self.pool.close())
             .await
-            .expect("Couldn't serve tcp listener and router");
+            .is_err()
+        {
+            log::warn!("Storage wasn't closed in {:?}", self.shutdown_timeout);
+        }
+        log::info!("Service stopped");
+
+        Ok(())
+    }
+}
+
+/// Generated document completed with the schemas of the registered sports
+fn api_doc(registry: &SportRegistry) -> openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    document_sports(&mut doc, registry);
+    document_security(&mut doc);
+
+    doc
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Couldn't listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                log::error!("Couldn't listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => log::info!("SIGINT received"),
+        _ = terminate => log::info!("SIGTERM received"),
     }
 }
 
-async fn retry_to_bind(url: &Url) -> Result<TcpListener, ()> {
+async fn retry_to_bind(url: &Url) -> Result<TcpListener, StartupError> {
+    let mut last_error = None;
     for _ in 0..5 {
         log::info!("Trying to bind service at {}", url);
         match TcpListener::bind(url.to_string()).await {
@@ -102,41 +279,458 @@ async fn retry_to_bind(url: &Url) -> Result<TcpListener, ()> {
             }
             Err(e) => {
                 log::error!("Tcp listener bind error: {}\n Retrying...", e);
+                last_error = Some(e);
             }
         }
         tokio::time::sleep(Duration::from_secs(1)).await;
     }
 
-    Err(())
+    Err(StartupError::Bind {
+        url: url.to_string(),
+        source: last_error.expect("Bind was attempted at least once"),
+    })
+}
+
+fn routes_health<S: Pool>(
+    tracker: Arc<PerformanceTracker>,
+    pool: Arc<S>,
+    started_at: Instant,
+) -> Router {
+    Router::new()
+        .route("/health/live", get(health_live))
+        .route("/health/ready", get(health_ready::<S>))
+        .layer(Extension((tracker, pool)))
+        .layer(Extension(started_at))
 }
 
 fn routes_get_performance(tracker: Arc<PerformanceTracker>) -> Router {
     Router::new()
+        .route("/sportsmen", get(get_sportsmen))
+        .route("/sportsmen/{name}", get(get_sportsman_profile))
+        .route("/{sport}", get(get_performances_by_sport))
+        .route("/{sport}/leaderboard", get(get_leaderboard_by_sport))
         .route("/{sport}/{name}", get(get_performance_by_sport))
+        .route("/{sport}/{name}/history", get(get_history_by_sport))
         .layer(Extension(tracker))
 }
 
-fn routes_add_performance(tracker: Arc<PerformanceTracker>, pool: Arc<DBPool>) -> Router {
+fn routes_add_performance<S: Pool>(tracker: Arc<PerformanceTracker>, pool: Arc<S>) -> Router {
     Router::new()
-        .route("/{sport}/{name}", post(add_performance_by_sport))
+        .route("/{sport}/{name}", post(add_performance_by_sport::<S>))
         .layer(Extension((tracker, pool)))
 }
 
-fn routes_remove_performance(tracker: Arc<PerformanceTracker>, pool: Arc<DBPool>) -> Router {
+fn routes_remove_performance<S: Pool>(tracker: Arc<PerformanceTracker>, pool: Arc<S>) -> Router {
     Router::new()
-        .route("/{sport}/{name}", delete(remove_performance_by_sport))
+        .route("/{sport}/{name}", delete(remove_performance_by_sport::<S>))
         .layer(Extension((tracker, pool)))
 }
 
+fn routes_import<S: Pool>(tracker: Arc<PerformanceTracker>, pool: Arc<S>) -> Router {
+    Router::new()
+        .route("/import/{sport}", post(import_performances::<S>))
+        .layer(Extension((tracker, pool)))
+}
+
+fn routes_sportsmen<S: Pool>(tracker: Arc<PerformanceTracker>, pool: Arc<S>) -> Router {
+    Router::new()
+        .route("/sportsmen", post(create_sportsman::<S>))
+        .route(
+            "/sportsmen/{name}",
+            patch(update_sportsman::<S>).delete(remove_sportsman::<S>),
+        )
+        .layer(Extension((tracker, pool)))
+}
+
+/// Unknown routes are reported as problem details like any other error
+async fn route_not_found(uri: Uri) -> Error {
+    Error::RouteNotFound(uri.path().to_string())
+}
+
+async fn method_not_allowed(method: Method) -> Error {
+    Error::MethodNotAllowed(method.to_string())
+}
+
+fn routes_audit<S: Pool>(pool: Arc<S>) -> Router {
+    Router::new()
+        .route("/audit", get(get_audit::<S>))
+        .layer(Extension(pool))
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "Сервис запущен / Service is running", body = Liveness, example = json!({
+            "status": "ok",
+            "version": "0.1.0",
+            "uptime_secs": 3600
+        }))
+    )
+)]
+async fn health_live(Extension(started_at): Extension<Instant>) -> impl IntoResponse {
+    Json(Liveness {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_secs: started_at.elapsed().as_secs(),
+    })
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Сервис готов принимать запросы / Service is ready to serve requests", body = Readiness, example = json!({
+            "status": "ready",
+            "version": "0.1.0",
+            "uptime_secs": 3600,
+            "checks": {
+                "database": "ok",
+                "tracker": "ok"
+            },
+            "sportsmen": 12
+        })),
+        (status = 503, description = "База данных недоступна или результаты не загружены / Database is unavailable or performances aren't loaded", body = Readiness, example = json!({
+            "status": "not_ready",
+            "version": "0.1.0",
+            "uptime_secs": 3600,
+            "checks": {
+                "database": "Storage unavailable: pool timed out while waiting for an open connection",
+                "tracker": "ok"
+            },
+            "sportsmen": 12
+        }))
+    )
+)]
+async fn health_ready<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    Extension(started_at): Extension<Instant>,
+) -> impl IntoResponse {
+    let database = pool.ping().await;
+    if let Err(e) = &database {
+        log::error!("Readiness check failed: {}", e);
+    }
+
+    let loaded = tracker.is_loaded();
+
+    let status = match database {
+        Ok(()) if loaded => StatusCode::OK,
+        _ => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (
+        status,
+        Json(Readiness {
+            status: if status == StatusCode::OK {
+                "ready"
+            } else {
+                "not_ready"
+            }
+            .to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: started_at.elapsed().as_secs(),
+            checks: ReadinessChecks {
+                database: database.map_or_else(|e| e.to_string(), |()| "ok".to_string()),
+                tracker: if loaded { "ok" } else { "loading" }.to_string(),
+            },
+            sportsmen: tracker.sportsmen_count().await,
+        }),
+    )
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/sportsmen",
+    tag = "sportsmen",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "Список спортсменов / List of sportsmen", body = Page<SportsmanOverview>, example = json!({
+            "total": 1,
+            "offset": 0,
+            "limit": 50,
+            "items": [
+                {
+                    "id": 1,
+                    "name": "John",
+                    "birth_date": "1998-04-21",
+                    "age": 26,
+                    "sex": "male",
+                    "body_mass": 72.5,
+                    "nationality": "NOR",
+                    "club": "Oslo Skiklubb",
+                    "sports": ["biathlon", "running"],
+                    "last_recorded_at": "2025-03-08T10:00:00+00:00"
+                }
+            ]
+        })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:malformed_request",
+            "title": "Malformed request",
+            "status": 400,
+            "detail": "Malformed request: Failed to deserialize query string: limit: invalid digit found in string",
+            "code": "malformed_request",
+            "instance": "/sportsmen",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn get_sportsmen(
+    Extension(tracker): Extension<Arc<PerformanceTracker>>,
+    caller: Caller,
+    ApiQuery(query): ApiQuery<ListQuery>,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::ReadAll)?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let items = tracker
+        .get_sportsmen()
+        .await
+        .into_iter()
+        .map(|summary| {
+            let overview = SportsmanOverview {
+                profile: SportsmanProfile::from(&summary.sportsman),
+                sports: summary.sports.iter().map(|s| s.to_string()).collect(),
+                last_recorded_at: summary.last_recorded_at,
+            };
+
+            ListItem {
+                name: summary.sportsman.name(),
+                recorded_at: summary.last_recorded_at,
+                json: serde_json::to_value(overview)
+                    .expect("Sportsman fields are plain numbers and strings"),
+            }
+        })
+        .collect();
+
+    Ok(query.apply(items))
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/sportsmen/{name}",
+    tag = "sportsmen",
+    params(
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name")
+    ),
+    responses(
+        (status = 200, description = "Профиль спортсмена / Sportsman profile", body = SportsmanProfile, example = json!({
+            "id": 1,
+            "name": "John",
+            "birth_date": "1998-04-21",
+            "age": 26,
+            "sex": "male",
+            "body_mass": 72.5,
+            "nationality": "NOR",
+            "club": "Oslo Skiklubb"
+        })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sportsman_not_found",
+            "title": "Sportsman not found",
+            "status": 404,
+            "detail": "Sportsman not found",
+            "code": "sportsman_not_found",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn get_sportsman_profile(
+    Extension(tracker): Extension<Arc<PerformanceTracker>>,
+    caller: Caller,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Read(&name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sportsman = Sportsman::new(name)?;
+    let sportsman = tracker.get_sportsman(&sportsman).await?;
+
+    Ok(Json(SportsmanProfile::from(&sportsman)))
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/{sport}",
+    tag = "performances",
+    params(
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)"),
+        ListQuery,
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Последние результаты всех спортсменов / Latest performances of all sportsmen", body = Page<LatestPerformance<Performance>>, example = json!({
+            "total": 1,
+            "offset": 0,
+            "limit": 50,
+            "items": [
+                {
+                    "name": "John",
+                    "performance": {
+                        "distance": 999.9,
+                        "speed": 123.2,
+                        "recorded_at": "2025-03-08T10:00:00+00:00"
+                    }
+                }
+            ]
+        })),
+        (status = 404, description = "Вид спорта не найден / Sport not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sport_not_found",
+            "title": "Sport not found",
+            "status": 404,
+            "detail": "Unknown sport tennis",
+            "code": "sport_not_found",
+            "instance": "/tennis",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 406, description = "Неизвестная система единиц в Accept / Unknown units in Accept", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:not_acceptable",
+            "title": "Not acceptable",
+            "status": 406,
+            "detail": "Not acceptable: Unknown units nautical, expected one of: metric, imperial",
+            "code": "not_acceptable",
+            "instance": "/running",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn get_performances_by_sport(
+    Extension(tracker): Extension<Arc<PerformanceTracker>>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
+    Path(sport): Path<String>,
+    ApiQuery(query): ApiQuery<ListQuery>,
+    units: OutputUnits,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::ReadAll)?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+
+    let items = tracker
+        .get_latest_performances(sport.name)
+        .await
+        .into_iter()
+        .map(|(sportsman, record)| {
+            let mut performance = record.to_json(&sportsman);
+            sport.show_units(&mut performance, units.0);
+
+            ListItem {
+                name: sportsman.name(),
+                recorded_at: Some(record.recorded_at),
+                json: json!({
+                    "name": sportsman.name(),
+                    "performance": performance,
+                }),
+            }
+        })
+        .collect();
+
+    Ok(units.respond(query.apply(items)))
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/{sport}/leaderboard",
+    tag = "performances",
+    params(
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)"),
+        LeaderboardQuery,
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Рейтинг спортсменов по лучшему результату / Sportsmen ranked by their best performance", body = Ranking, example = json!({
+            "sport": "running",
+            "by": "speed",
+            "order": "desc",
+            "unit": "km/h",
+            "entries": [
+                { "rank": 1, "name": "John", "value": 25.1, "recorded_at": "2025-03-08T10:00:00+00:00" },
+                { "rank": 2, "name": "Ann", "value": 23.4, "recorded_at": "2025-03-01T10:00:00+00:00" },
+                { "rank": 2, "name": "Bob", "value": 23.4, "recorded_at": "2025-03-02T10:00:00+00:00" }
+            ]
+        })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:invalid_ranking_field",
+            "title": "Invalid ranking field",
+            "status": 400,
+            "detail": "Invalid ranking field, expected one of: distance, speed",
+            "code": "invalid_ranking_field",
+            "instance": "/running/leaderboard",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e",
+            "expected": ["distance", "speed"]
+        })),
+        (status = 404, description = "Вид спорта не найден / Sport not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sport_not_found",
+            "title": "Sport not found",
+            "status": 404,
+            "detail": "Unknown sport tennis",
+            "code": "sport_not_found",
+            "instance": "/tennis/leaderboard",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 406, description = "Неизвестная система единиц в Accept / Unknown units in Accept", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:not_acceptable",
+            "title": "Not acceptable",
+            "status": 406,
+            "detail": "Not acceptable: Unknown units nautical, expected one of: metric, imperial",
+            "code": "not_acceptable",
+            "instance": "/running/leaderboard",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn get_leaderboard_by_sport(
+    Extension(tracker): Extension<Arc<PerformanceTracker>>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
+    Path(sport): Path<String>,
+    ApiQuery(query): ApiQuery<LeaderboardQuery>,
+    units: OutputUnits,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::ReadAll)?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+
+    if !sport.ranking_fields.contains(&query.by.as_str()) {
+        return Err(Error::InvalidRankingField(sport.ranking_fields.clone()));
+    }
+
+    let histories = tracker.get_histories(sport.name).await;
+
+    let leaderboard = Leaderboard::new(
+        sport.name.to_string(),
+        query.by.clone(),
+        query.descending(),
+        query.limit(),
+        histories,
+    );
+
+    Ok(units.respond(leaderboard.in_units(sport.units_of(&query.by), units.0)))
+}
+
 #[utoipa::path(
     method(get),
     path = "/{sport}/{name}",
+    tag = "performances",
     params(
-        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting)"),
-        ("name" = String, Path, description = "Имя спортсмена")
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)"),
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name"),
+        UnitsQuery
     ),
     responses(
-        (status = 200, description = "Успешный ответ", body = serde_json::Value, examples(
+        (status = 200, description = "Успешный ответ / Successful response", body = Performance, examples(
             ("running_example" = (summary = "Running example", value = json!({
                 "distance": 999.9,
                 "speed": 123.2
@@ -149,48 +743,186 @@ fn routes_remove_performance(tracker: Arc<PerformanceTracker>, pool: Arc<DBPool>
             ("weight_lifting_example" = (summary = "Weight lifting example", value = json!({
                 "weight": 120,
                 "lifted_weight": 460
+            }))),
+            ("swimming_example" = (summary = "Swimming example", value = json!({
+                "distance": 400,
+                "time": 262.5,
+                "stroke": "freestyle",
+                "pool_length": 50
+            }))),
+            ("cycling_example" = (summary = "Cycling example, watts_per_kg is present when body mass is known", value = json!({
+                "distance": 42.2,
+                "duration": 4500,
+                "average_power": 245,
+                "average_cadence": 88,
+                "elevation_gain": 610,
+                "average_speed": 33.76,
+                "watts_per_kg": 3.3793104
             })))
         )),
-        (status = 400, description = "Плохой запрос", body = serde_json::Value, example = json!({ "message": "Name too long" })),
-        (status = 404, description = "Не найдено", body = serde_json::Value, example = json!({ "message": "Performance not found" }))
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:performance_not_found",
+            "title": "Performance not found",
+            "status": 404,
+            "detail": "Performance not found",
+            "code": "performance_not_found",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 406, description = "Неизвестная система единиц в Accept / Unknown units in Accept", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:not_acceptable",
+            "title": "Not acceptable",
+            "status": 406,
+            "detail": "Not acceptable: Unknown units nautical, expected one of: metric, imperial",
+            "code": "not_acceptable",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
     )
 )]
 async fn get_performance_by_sport(
     Extension(tracker): Extension<Arc<PerformanceTracker>>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
     Path((sport, name)): Path<(String, String)>,
-) -> impl IntoResponse {
-    match sport.as_str() {
-        "running" => get_performance::<Running>(Extension(tracker), Path(name))
-            .await
-            .into_response(),
-        "biathlon" => get_performance::<Biathlon>(Extension(tracker), Path(name))
-            .await
-            .into_response(),
-        "weight_lifting" => get_performance::<WeightLifting>(Extension(tracker), Path(name))
-            .await
-            .into_response(),
-        _ => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "message": "Invalid sport type" })),
-        )
-            .into_response(),
-    }
+    units: OutputUnits,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Read(&name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+    let sportsman = Sportsman::new(name)?;
+
+    let record = tracker.get_performance(&sportsman, sport.name).await?;
+    log::info!("Performance: {:?}", record.metric);
+
+    // stored sportsman has the profile needed for derived fields
+    let stored = tracker.get_sportsman(&sportsman).await?;
+    let mut json = record.metric.to_json_for(stored.profile());
+    sport.show_units(&mut json, units.0);
+
+    Ok(units.respond(Json(json)))
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/{sport}/{name}/history",
+    tag = "performances",
+    params(
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)"),
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name"),
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Все результаты от самого старого к последнему / All performances from the oldest to the latest", body = [Recorded<Performance>], examples(
+            ("running_example" = (summary = "Running example", value = json!([
+                {
+                    "distance": 999.9,
+                    "speed": 123.2,
+                    "recorded_at": "2025-03-01T10:00:00+00:00"
+                },
+                {
+                    "distance": 1000.5,
+                    "speed": 125.0,
+                    "recorded_at": "2025-03-08T10:00:00+00:00"
+                }
+            ]))),
+            ("swimming_example" = (summary = "Swimming example", value = json!([
+                {
+                    "distance": 400,
+                    "time": 262.5,
+                    "stroke": "freestyle",
+                    "pool_length": 50,
+                    "recorded_at": "2025-03-08T10:00:00+00:00"
+                }
+            ])))
+        )),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/running/John/history",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:performance_not_found",
+            "title": "Performance not found",
+            "status": 404,
+            "detail": "Performance not found",
+            "code": "performance_not_found",
+            "instance": "/running/John/history",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 406, description = "Неизвестная система единиц в Accept / Unknown units in Accept", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:not_acceptable",
+            "title": "Not acceptable",
+            "status": 406,
+            "detail": "Not acceptable: Unknown units nautical, expected one of: metric, imperial",
+            "code": "not_acceptable",
+            "instance": "/running/John/history",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn get_history_by_sport(
+    Extension(tracker): Extension<Arc<PerformanceTracker>>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
+    Path((sport, name)): Path<(String, String)>,
+    units: OutputUnits,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Read(&name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+    let sportsman = Sportsman::new(name)?;
+
+    let history = tracker.get_history(&sportsman, sport.name).await?;
+    log::info!("History length: {}", history.len());
+    let stored = tracker.get_sportsman(&sportsman).await?;
+
+    let records: Vec<_> = history
+        .iter()
+        .map(|record| {
+            let mut json = record.to_json(&stored);
+            sport.show_units(&mut json, units.0);
+            json
+        })
+        .collect();
+
+    Ok(units.respond(Json(records)))
 }
 
 #[utoipa::path(
     method(post),
     path = "/{sport}/{name}",
+    tag = "performances",
     params(
-        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting)"),
-        ("name" = String, Path, description = "Имя спортсмена")
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)"),
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name")
     ),
     request_body(
-        content = serde_json::Value,
+        content = PerformanceRequest,
         examples(
         ("running_example" = (summary = "Running example", value = json!({
             "distance": 999.9,
             "speed": 123.2
         }))),
+        ("running_units_example" = (summary = "Running example with units, plain numbers are in km and km/h", value = json!({
+            "distance": { "value": 3.1, "unit": "mi" },
+            "speed": { "value": 7.5, "unit": "mph" }
+        }))),
         ("biathlon_example" = (summary = "Biathlon example", value = json!({
             "accuracy": 18.9,
             "distance": 20.3,
@@ -199,188 +931,613 @@ async fn get_performance_by_sport(
         ("weight_lifting_example" = (summary = "Weight lifting example", value = json!({
             "weight": 120,
             "lifted_weight": 460
+        }))),
+        ("swimming_example" = (summary = "Swimming example", value = json!({
+            "distance": 400,
+            "time": 262.5,
+            "stroke": "freestyle",
+            "pool_length": 50
+        }))),
+        ("cycling_example" = (summary = "Cycling example", value = json!({
+            "distance": 42.2,
+            "duration": 4500,
+            "average_power": 245,
+            "average_cadence": 88,
+            "elevation_gain": 610
         })))
         )
     ),
     responses(
-        (status = 200, description = "Успешный ответ", body = serde_json::Value, example = json!({"message": "Performance added successfully"})),
-        (status = 400, description = "Плохой запрос", body = serde_json::Value, example = json!({ "message": "Invalid sport type or malformed request" })),
-        (status = 404, description = "Не найдено", body = serde_json::Value, example = json!({ "message": "Performance not found" })),
-        (status = 500, description = "Ошибка сервера", body = serde_json::Value, example = json!({ "message": "Something went wrong" }))
+        (status = 200, description = "Успешный ответ / Successful response", body = Message, example = json!({ "message": "Running performance added successfully" })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:malformed_request",
+            "title": "Malformed request",
+            "status": 400,
+            "detail": "Malformed request: Failed to parse the request body as JSON: expected value at line 1 column 1",
+            "code": "malformed_request",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Вид спорта не найден / Sport not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sport_not_found",
+            "title": "Sport not found",
+            "status": 404,
+            "detail": "Unknown sport tennis",
+            "code": "sport_not_found",
+            "instance": "/tennis/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 415, description = "Тело запроса не JSON / Request body isn't JSON", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:unsupported_media_type",
+            "title": "Unsupported media type",
+            "status": 415,
+            "detail": "Expected request with `Content-Type: application/json`",
+            "code": "unsupported_media_type",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 422, description = "Недопустимые значения полей / Invalid field values", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:invalid_performance",
+            "title": "Invalid performance",
+            "status": 422,
+            "detail": "Invalid BiathlonPerformance: 2 invalid fields",
+            "code": "invalid_performance",
+            "instance": "/biathlon/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e",
+            "errors": [
+                { "field": "accuracy", "reason": "must be between 0 and 100" },
+                { "field": "speed", "reason": "must be positive" }
+            ]
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
     )
 )]
-
-async fn add_performance_by_sport(
-    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<DBPool>)>,
+async fn add_performance_by_sport<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
     Path((sport, name)): Path<(String, String)>,
-    body: Json<serde_json::Value>,
-) -> impl IntoResponse {
-    match sport.as_str() {
-        "running" => match serde_json::from_value::<RunningPerformance>(body.0) {
-            Ok(performance) => add_performance::<Running, RunningPerformance>(
-                Extension((tracker, pool)),
-                Path(name),
-                Json(performance),
-            )
-            .await
-            .into_response(),
-            Err(_) => Responses::InvalidPerformanceFormat("RunningPerformance").into_response(),
-        },
-        "biathlon" => match serde_json::from_value::<BiathlonPerformance>(body.0) {
-            Ok(performance) => add_performance::<Biathlon, BiathlonPerformance>(
-                Extension((tracker, pool)),
-                Path(name),
-                Json(performance),
-            )
-            .await
-            .into_response(),
-            Err(_) => Responses::InvalidPerformanceFormat("BiathlonPerformance").into_response(),
-        },
-        "weight_lifting" => match serde_json::from_value::<WeightLiftingPerformance>(body.0) {
-            Ok(performance) => add_performance::<WeightLifting, WeightLiftingPerformance>(
-                Extension((tracker, pool)),
-                Path(name),
-                Json(performance),
-            )
-            .await
-            .into_response(),
-            Err(_) => {
-                Responses::InvalidPerformanceFormat("WeightLiftingPerformance").into_response()
-            }
-        },
-        _ => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "message": "Invalid sport type" })),
-        )
-            .into_response(),
-    }
+    ApiJson(body): ApiJson<serde_json::Value>,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Write(&name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+    let metric = sport
+        .parse_request(body)
+        .map_err(|errors| Error::InvalidPerformance {
+            model: sport.request_name,
+            errors,
+        })?;
+    let sportsman = Sportsman::new(name)?;
+    let response_name = metric.response_name();
+
+    // Postgres keeps timestamps with microsecond precision
+    let record = Record::new(metric, Utc::now().trunc_subsecs(6));
+    let latest = tracker.get_performance(&sportsman, sport.name).await.ok();
+    let audit = AuditEntry::added(
+        &caller.name,
+        &sportsman,
+        sport.name,
+        latest.as_ref(),
+        &record,
+    );
+
+    // cache is updated only after the transaction is committed
+    let id = pool
+        .add_performance(&caller.tenant, &sportsman, sport, &record, &audit)
+        .await?;
+
+    tracker.add_performance(sportsman.with_id(id), record).await;
+    log::info!("Performance was added successfully");
+
+    Ok(Responses::PerformanceAdded(response_name))
 }
 
 #[utoipa::path(
     method(delete),
     path = "/{sport}/{name}",
+    tag = "performances",
     params(
-        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting)"),
-        ("name" = String, Path, description = "Имя спортсмена")
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)"),
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name")
     ),
     responses(
-        (status = 200, description = "Успешный ответ", body = serde_json::Value, example = json!({ "message": "Performance removed successfully" })),
-        (status = 400, description = "Плохой запрос", body = serde_json::Value, example = json!({ "message": "Name too long" })),
-        (status = 404, description = "Не найдено", body = serde_json::Value, example = json!({ "message": "Performance not found" })),
-        (status = 500, description = "Ошибка сервера", body = serde_json::Value, example = json!({ "message": "Something went wrong" }))
+        (status = 200, description = "Успешный ответ / Successful response", body = Message, example = json!({ "message": "Performance removed successfully" })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:performance_not_found",
+            "title": "Performance not found",
+            "status": 404,
+            "detail": "Performance not found",
+            "code": "performance_not_found",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/running/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
     )
 )]
-async fn remove_performance_by_sport(
-    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<DBPool>)>,
+async fn remove_performance_by_sport<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
     Path((sport, name)): Path<(String, String)>,
-) -> impl IntoResponse {
-    match sport.as_str() {
-        "running" => remove_performance::<Running>(Extension((tracker, pool)), Path(name))
-            .await
-            .into_response(),
-        "biathlon" => remove_performance::<Biathlon>(Extension((tracker, pool)), Path(name))
-            .await
-            .into_response(),
-        "weight_lifting" => {
-            remove_performance::<WeightLifting>(Extension((tracker, pool)), Path(name))
-                .await
-                .into_response()
-        }
-        _ => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "message": "Invalid sport type" })),
-        )
-            .into_response(),
-    }
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Write(&name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+    let sportsman = Sportsman::new(name)?;
+    let history = tracker
+        .get_history(&sportsman, sport.name)
+        .await
+        .unwrap_or_default();
+    let audit = AuditEntry::removed(
+        Operation::Remove,
+        &caller.name,
+        &sportsman,
+        sport.name,
+        &history,
+    );
+
+    pool.remove_performance(&caller.tenant, &sportsman, sport, &audit)
+        .await?;
+    tracker.remove_performance(sportsman, sport.name).await?;
+    log::info!("Performance was removed successfully");
+
+    Ok(Responses::PerformanceRemoved)
 }
 
-async fn get_performance<T: Metric + Clone>(
-    Extension(tracker): Extension<Arc<PerformanceTracker>>,
-    Path(name): Path<String>,
-) -> impl IntoResponse {
-    let sportsman = match Sportsman::new(name) {
-        Ok(s) => s,
-        Err(e) => return e.into_response(),
-    };
+#[utoipa::path(
+    method(post),
+    path = "/import/{sport}",
+    tag = "performances",
+    params(
+        ("sport" = String, Path, description = "Вид спорта (running, biathlon, weight_lifting, swimming, cycling или заданный в SPORTS_SCHEMA) / Sport (running, biathlon, weight_lifting, swimming, cycling or one defined in SPORTS_SCHEMA)")
+    ),
+    request_body(
+        description = "Столбец name, столбцы полей вида спорта и необязательный recorded_at / Column name, columns of the sport's fields and optional recorded_at",
+        content = String,
+        content_type = "text/csv",
+        example = "name,distance,speed,recorded_at\nJohn,10,12.5,2025-03-08T10:00:00Z\nAnn,-5,11\n"
+    ),
+    responses(
+        (status = 200, description = "Верные строки сохранены, остальные отклонены / Valid rows stored, the rest rejected", body = ImportReport, example = json!({
+            "imported": 1,
+            "rejected": [
+                {
+                    "line": 3,
+                    "name": "Ann",
+                    "errors": [
+                        { "field": "distance", "reason": "must be positive" }
+                    ]
+                }
+            ]
+        })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:malformed_request",
+            "title": "Malformed request",
+            "status": 400,
+            "detail": "Malformed request: Unknown column pace of running, expected name, distance, speed and optional recorded_at",
+            "code": "malformed_request",
+            "instance": "/import/running",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sport_not_found",
+            "title": "Sport not found",
+            "status": 404,
+            "detail": "Unknown sport tennis",
+            "code": "sport_not_found",
+            "instance": "/import/tennis",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 415, description = "Тело запроса не CSV / Request body isn't CSV", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:unsupported_media_type",
+            "title": "Unsupported media type",
+            "status": 415,
+            "detail": "Expected request with `Content-Type: text/csv`",
+            "code": "unsupported_media_type",
+            "instance": "/import/running",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/import/running",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn import_performances<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
+    Path(sport): Path<String>,
+    CsvBody(body): CsvBody,
+) -> Result<impl IntoResponse, Error> {
+    // access is checked per row, so rows of other sportsmen are rejected rather than the file
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sport = registry.find(&sport)?;
+    let rows = import::read_csv(sport, &body)?;
 
-    match tracker.get_performance::<T>(&sportsman).await {
-        Ok(performance) => {
-            log::info!("Performance: {:?}", performance);
-            performance.into_response()
+    // Postgres keeps timestamps with microsecond precision
+    let now = Utc::now().trunc_subsecs(6);
+    let mut records = Vec::new();
+    let mut rejected = Vec::new();
+    for row in rows {
+        let (line, name) = (row.line, row.name.clone());
+        match row.read(sport, &caller, now) {
+            Ok(record) => records.push(record),
+            Err(errors) => rejected.push(RejectedRow { line, name, errors }),
         }
-        Err(e) => {
-            log::info!("{}", e);
-            Responses::Errors(e).into_response()
+    }
+
+    // a row replaces the latest record including the ones imported before it
+    let mut latest: HashMap<Sportsman, Record> = HashMap::new();
+    let mut audit = Vec::with_capacity(records.len());
+    for (sportsman, record) in &records {
+        let previous = match latest.get(sportsman) {
+            Some(previous) => Some(previous.clone()),
+            None => tracker.get_performance(sportsman, sport.name).await.ok(),
+        };
+        // a backdated row goes into the history without replacing the latest record
+        let replaces = previous
+            .as_ref()
+            .is_none_or(|previous| previous.recorded_at <= record.recorded_at);
+        audit.push(AuditEntry::added(
+            &caller.name,
+            sportsman,
+            sport.name,
+            previous.as_ref().filter(|_| replaces),
+            record,
+        ));
+        if replaces {
+            latest.insert(sportsman.clone(), record.clone());
         }
     }
-}
 
-async fn add_performance<T, P>(
-    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<DBPool>)>,
-    Path(name): Path<String>,
-    Json(performance): Json<P>,
-) -> impl IntoResponse
-where
-    T: Metric,
-    P: Into<T>,
-{
-    let sportsman = match Sportsman::new(name) {
-        Ok(s) => s,
-        Err(e) => return e.into_response(),
-    };
-    let metric: T = performance.into();
-    let response_name = metric.response_name();
+    // cache is updated only after the transaction is committed
+    let ids = pool
+        .import_performances(&caller.tenant, sport, &records, &audit)
+        .await?;
 
-    if let Err(e) = pool.add_sportsman(&sportsman).await {
-        log::error!("Error while saving sportsman: {e}");
-        return Responses::Errors(Error::SaveError).into_response();
+    let imported = records.len();
+    for ((sportsman, record), id) in records.into_iter().zip(ids) {
+        tracker.add_performance(sportsman.with_id(id), record).await;
     }
+    log::info!(
+        "{imported} performances were imported, {} rows were rejected",
+        rejected.len()
+    );
 
-    if let Err(e) = pool.add_performance(&sportsman, metric.clone_box()).await {
-        log::error!("Error while saving performance{e}");
-        return Responses::Errors(Error::SaveError).into_response();
-    }
+    Ok(Json(ImportReport { imported, rejected }))
+}
 
-    tracker.add_performance(sportsman, metric.clone_box()).await;
-    log::info!("Performance was added successfully");
+#[utoipa::path(
+    method(post),
+    path = "/sportsmen",
+    tag = "sportsmen",
+    request_body(
+        content = NewSportsman,
+        example = json!({
+            "name": "John",
+            "birth_date": "1998-04-21",
+            "sex": "male",
+            "body_mass": 72.5,
+            "nationality": "NOR",
+            "club": "Oslo Skiklubb"
+        })
+    ),
+    responses(
+        (status = 201, description = "Спортсмен создан / Sportsman created", body = Message, example = json!({ "message": "Sportsman created successfully" })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/sportsmen",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 409, description = "Спортсмен уже существует / Sportsman already exists", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sportsman_already_exists",
+            "title": "Sportsman already exists",
+            "status": 409,
+            "detail": "Sportsman already exists",
+            "code": "sportsman_already_exists",
+            "instance": "/sportsmen",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/sportsmen",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn create_sportsman<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    caller: Caller,
+    ApiJson(new): ApiJson<NewSportsman>,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Write(&new.name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sportsman = Sportsman::try_from(new)?;
+
+    let id = pool.create_sportsman(&caller.tenant, &sportsman).await?;
+    tracker.add_sportsman(sportsman.with_id(id)).await?;
+    log::info!("Sportsman was created successfully");
 
-    Responses::PerformanceAdded(response_name).into_response()
+    Ok(Responses::SportsmanCreated)
 }
 
-async fn remove_performance<T: Metric>(
-    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<DBPool>)>,
+#[utoipa::path(
+    method(patch),
+    path = "/sportsmen/{name}",
+    tag = "sportsmen",
+    params(
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name")
+    ),
+    request_body(
+        content = SportsmanUpdate,
+        example = json!({ "name": "John Smith", "club": "Bergen IL", "body_mass": null })
+    ),
+    responses(
+        (status = 200, description = "Спортсмен обновлен / Sportsman updated", body = Message, example = json!({ "message": "Sportsman updated successfully" })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sportsman_not_found",
+            "title": "Sportsman not found",
+            "status": 404,
+            "detail": "Sportsman not found",
+            "code": "sportsman_not_found",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 409, description = "Имя уже занято / Name is already taken", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sportsman_already_exists",
+            "title": "Sportsman already exists",
+            "status": 409,
+            "detail": "Sportsman already exists",
+            "code": "sportsman_already_exists",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn update_sportsman<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
     Path(name): Path<String>,
-) -> impl IntoResponse {
-    let sportsman = match Sportsman::new(name) {
-        Ok(s) => s,
-        Err(e) => return e.into_response(),
-    };
+    ApiJson(update): ApiJson<SportsmanUpdate>,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Write(&name))?;
+    // renamed sportsman must stay accessible to the caller
+    if let Some(new_name) = &update.name {
+        caller.authorize(Access::Write(new_name))?;
+    }
+    let tracker = tracker.partition(&caller.tenant).await;
 
-    match pool.remove_performance::<T>(&sportsman).await {
-        Ok(removed) => {
-            if !removed {
-                return Responses::PerformanceNotFound.into_response();
-            }
-        }
-        Err(e) => {
-            return match e {
-                sqlx::Error::RowNotFound => Responses::SportsmanNotFound.into_response(),
-                _ => {
-                    log::error!("Error while removing performance: {e}");
-                    Responses::Errors(Error::RemoveError).into_response()
-                }
+    let sportsman = Sportsman::new(name)?;
+
+    let stored = tracker.get_sportsman(&sportsman).await?;
+    let updated = update.apply(stored)?;
+
+    let mut audit = Vec::new();
+    if updated.name() != sportsman.name() {
+        for sport in registry.iter() {
+            if tracker.get_history(&sportsman, sport.name).await.is_ok() {
+                audit.push(AuditEntry::renamed(
+                    &caller.name,
+                    &sportsman,
+                    &updated,
+                    sport.name,
+                ));
             }
         }
     }
 
-    match tracker.remove_performance::<T>(sportsman).await {
-        Ok(_) => {
-            log::info!("Performance was removed successfully");
-            Responses::PerformanceRemoved.into_response()
-        }
-        Err(e) => {
-            log::info!("{}", e);
-            Responses::Errors(e).into_response()
+    pool.update_sportsman(&caller.tenant, &sportsman, &updated, &audit)
+        .await?;
+    tracker.update_sportsman(&sportsman, updated).await?;
+    log::info!("Sportsman was updated successfully");
+
+    Ok(Responses::SportsmanUpdated)
+}
+
+#[utoipa::path(
+    method(delete),
+    path = "/sportsmen/{name}",
+    tag = "sportsmen",
+    params(
+        ("name" = String, Path, description = "Имя спортсмена / Sportsman name")
+    ),
+    responses(
+        (status = 200, description = "Спортсмен и все его результаты удалены / Sportsman and all their performances removed", body = Message, example = json!({ "message": "Sportsman removed successfully" })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:name_too_long",
+            "title": "Sportsman name is too long",
+            "status": 400,
+            "detail": "Sportsman name is too long",
+            "code": "name_too_long",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 404, description = "Не найдено / Not found", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:sportsman_not_found",
+            "title": "Sportsman not found",
+            "status": 404,
+            "detail": "Sportsman not found",
+            "code": "sportsman_not_found",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/sportsmen/John",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn remove_sportsman<S: Pool>(
+    Extension((tracker, pool)): Extension<(Arc<PerformanceTracker>, Arc<S>)>,
+    Extension(registry): Extension<Arc<SportRegistry>>,
+    caller: Caller,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    caller.authorize(Access::Remove(&name))?;
+    let tracker = tracker.partition(&caller.tenant).await;
+
+    let sportsman = Sportsman::new(name)?;
+
+    let mut audit = Vec::new();
+    for sport in registry.iter() {
+        if let Ok(history) = tracker.get_history(&sportsman, sport.name).await {
+            audit.push(AuditEntry::removed(
+                Operation::RemoveSportsman,
+                &caller.name,
+                &sportsman,
+                sport.name,
+                &history,
+            ));
         }
     }
+
+    pool.remove_sportsman(&caller.tenant, &sportsman, &audit)
+        .await?;
+    tracker.remove_sportsman(&sportsman).await?;
+    log::info!("Sportsman was removed successfully");
+
+    Ok(Responses::SportsmanRemoved)
+}
+
+#[utoipa::path(
+    method(get),
+    path = "/audit",
+    tag = "audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, description = "Изменения результатов от старых к новым / Changes of performances from the oldest", body = Page<AuditRecord>, example = json!({
+            "total": 2,
+            "offset": 0,
+            "limit": 50,
+            "items": [
+                {
+                    "actor": "coach",
+                    "operation": "add",
+                    "sport": "running",
+                    "sportsman": "John",
+                    "before": null,
+                    "after": { "distance": 10.0, "speed": 12.5, "recorded_at": "2025-03-08T10:00:00+00:00" },
+                    "recorded_at": "2025-03-08T10:00:00+00:00"
+                },
+                {
+                    "actor": "admin",
+                    "operation": "remove",
+                    "sport": "running",
+                    "sportsman": "John",
+                    "before": [{ "distance": 10.0, "speed": 12.5, "recorded_at": "2025-03-08T10:00:00+00:00" }],
+                    "after": null,
+                    "recorded_at": "2025-03-09T08:30:00+00:00"
+                }
+            ]
+        })),
+        (status = 400, description = "Плохой запрос / Bad request", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:malformed_request",
+            "title": "Malformed request",
+            "status": 400,
+            "detail": "Malformed request: Failed to deserialize query string: since: input contains invalid characters",
+            "code": "malformed_request",
+            "instance": "/audit",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        })),
+        (status = 500, description = "Ошибка сервера / Server error", body = Problem, content_type = "application/problem+json", example = json!({
+            "type": "urn:athlete-tracker:problem:storage_error",
+            "title": "Something went wrong",
+            "status": 500,
+            "detail": "Something went wrong",
+            "code": "storage_error",
+            "instance": "/audit",
+            "request_id": "3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e"
+        }))
+    )
+)]
+async fn get_audit<S: Pool>(
+    Extension(pool): Extension<Arc<S>>,
+    caller: Caller,
+    ApiQuery(query): ApiQuery<AuditQuery>,
+) -> Result<impl IntoResponse, Error> {
+    match &query.sportsman {
+        Some(name) => caller.authorize(Access::Read(name))?,
+        None => caller.authorize(Access::ReadAll)?,
+    }
+
+    let page = pool.get_audit(&caller.tenant, &query.filter()).await?;
+
+    Ok(Page {
+        total: page.total,
+        offset: page.offset,
+        limit: page.limit,
+        items: page
+            .items
+            .into_iter()
+            .map(AuditRecord::from)
+            .collect::<Vec<_>>(),
+    })
 }
@@ -0,0 +1,130 @@
+use crate::models::error::Error;
+use crate::models::units::UnitSystem;
+use crate::service::models::UnitsQuery;
+use axum::body::Bytes;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Query, Request};
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::http::request::Parts;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::de::DeserializeOwned;
+
+/// [`Json`] reporting rejections as problem details
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::<T>::from_request(request, state).await {
+            Ok(Json(value)) => Ok(Self(value)),
+            Err(JsonRejection::MissingJsonContentType(e)) => {
+                Err(Error::UnsupportedMediaType(e.body_text()))
+            }
+            Err(e) => Err(Error::MalformedRequest(e.body_text())),
+        }
+    }
+}
+
+/// Body of `text/csv` request
+pub struct CsvBody(pub Bytes);
+
+impl<S> FromRequest<S> for CsvBody
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_csv = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/csv"));
+        if !is_csv {
+            return Err(Error::UnsupportedMediaType(
+                "Expected request with `Content-Type: text/csv`".to_string(),
+            ));
+        }
+
+        Bytes::from_request(request, state)
+            .await
+            .map(Self)
+            .map_err(|e| Error::MalformedRequest(e.body_text()))
+    }
+}
+
+/// [`Query`] reporting rejections as problem details
+pub struct ApiQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ApiQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Query::<T>::from_request_parts(parts, state)
+            .await
+            .map(|Query(value)| Self(value))
+            .map_err(|e: QueryRejection| Error::MalformedRequest(e.body_text()))
+    }
+}
+
+/// Units the measured values are shown in. `units` query parameter wins over
+/// `units` parameter of `Accept` header, metric units are used if neither is given
+#[derive(Debug, Clone, Copy)]
+pub struct OutputUnits(pub UnitSystem);
+
+impl<S> FromRequestParts<S> for OutputUnits
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ApiQuery(query) = ApiQuery::<UnitsQuery>::from_request_parts(parts, state).await?;
+        if let Some(units) = query.units {
+            return Ok(Self(units));
+        }
+
+        let accept = parts
+            .headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+        for media_range in accept.flat_map(|value| value.split(',')) {
+            for param in media_range.split(';').skip(1) {
+                if let Some(("units", value)) = param.split_once('=').map(|(k, v)| (k.trim(), v)) {
+                    return value
+                        .trim()
+                        .trim_matches('"')
+                        .parse()
+                        .map(Self)
+                        .map_err(Error::NotAcceptable);
+                }
+            }
+        }
+
+        Ok(Self(UnitSystem::default()))
+    }
+}
+
+impl OutputUnits {
+    /// Response is marked with the units it is in, e.g. `application/json; units=imperial`
+    pub fn respond(self, body: impl IntoResponse) -> Response {
+        let content_type = format!("application/json; units={}", self.0);
+        let mut response = body.into_response();
+        if let Ok(value) = HeaderValue::from_str(&content_type) {
+            response.headers_mut().insert(CONTENT_TYPE, value);
+        }
+        response
+    }
+}
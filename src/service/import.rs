@@ -0,0 +1,177 @@
+use crate::models::error::Error;
+use crate::models::performance_tracker::Record;
+use crate::models::sportsman::Sportsman;
+use crate::models::validation::FieldError;
+use crate::service::auth::{Access, Caller};
+use crate::service::registry::{ColumnType, Sport};
+use chrono::{DateTime, NaiveDate, SubsecRound, Utc};
+use serde_json::{json, Map, Value};
+
+/// Column with the name of the sportsman
+const NAME: &str = "name";
+/// Optional column with the time of the session, the time of the import by default
+const RECORDED_AT: &str = "recorded_at";
+
+/// Row of the imported file, read as the body of `POST /{sport}/{name}`
+#[derive(Debug)]
+pub struct ImportRow {
+    /// Line of the row in the file, the header is line 1
+    pub line: u64,
+    pub name: String,
+    performance: Map<String, Value>,
+    recorded_at: Option<String>,
+}
+
+/// Reads the file with `name` column and columns of the sport's fields.
+/// Numeric cells become numbers, empty cells are left out so the request model reports them
+pub fn read_csv(sport: &Sport, body: &[u8]) -> Result<Vec<ImportRow>, Error> {
+    let malformed = |e: csv::Error| Error::MalformedRequest(e.to_string());
+
+    // spreadsheets leave out trailing empty cells, missing cells are reported as missing fields
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(body);
+    let headers = reader.headers().map_err(malformed)?.clone();
+
+    let expected = || {
+        let fields: Vec<&str> = sport.columns.iter().map(|c| c.name).collect();
+        format!(
+            "expected {NAME}, {} and optional {RECORDED_AT}",
+            fields.join(", ")
+        )
+    };
+    let mut columns = Vec::with_capacity(headers.len());
+    for header in &headers {
+        let column = match header {
+            NAME | RECORDED_AT => None,
+            _ => Some(
+                sport
+                    .columns
+                    .iter()
+                    .find(|c| c.name == header)
+                    .ok_or_else(|| {
+                        Error::MalformedRequest(format!(
+                            "Unknown column {header} of {}, {}",
+                            sport.name,
+                            expected()
+                        ))
+                    })?,
+            ),
+        };
+        columns.push((header, column));
+    }
+    if !headers.iter().any(|header| header == NAME) {
+        return Err(Error::MalformedRequest(format!(
+            "Missing column {NAME}, {}",
+            expected()
+        )));
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(malformed)?;
+        let mut row = ImportRow {
+            line: record.position().map_or(0, |p| p.line()),
+            name: String::new(),
+            performance: Map::new(),
+            recorded_at: None,
+        };
+
+        for ((header, column), cell) in columns.iter().zip(record.iter()) {
+            match (*header, column) {
+                (NAME, _) => row.name = cell.to_string(),
+                (RECORDED_AT, _) if !cell.is_empty() => row.recorded_at = Some(cell.to_string()),
+                (_, Some(column)) if !cell.is_empty() => {
+                    row.performance.insert(
+                        column.name.to_string(),
+                        cell_value(column.column_type, cell),
+                    );
+                }
+                _ => {}
+            }
+        }
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Integers are kept as such, so integer fields of the sports defined at runtime accept them
+fn cell_value(column_type: ColumnType, cell: &str) -> Value {
+    if column_type == ColumnType::Real {
+        if let Ok(value) = cell.parse::<i64>() {
+            return json!(value);
+        }
+        if let Ok(value) = cell.parse::<f64>() {
+            return json!(value);
+        }
+    }
+    Value::String(cell.to_string())
+}
+
+impl ImportRow {
+    /// Checks the row as the single performance would be checked, returns errors of all invalid fields
+    pub fn read(
+        self,
+        sport: &Sport,
+        caller: &Caller,
+        now: DateTime<Utc>,
+    ) -> Result<(Sportsman, Record), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        let sportsman = match Sportsman::new(self.name.clone()) {
+            Ok(sportsman) => match caller.authorize(Access::Write(&self.name)) {
+                Ok(()) => Some(sportsman),
+                Err(e) => {
+                    errors.push(FieldError::new(NAME, e.to_string()));
+                    None
+                }
+            },
+            Err(e) => {
+                errors.push(FieldError::new(NAME, e.to_string()));
+                None
+            }
+        };
+
+        let recorded_at = match self.recorded_at.as_deref().map(parse_time) {
+            None => Some(now),
+            Some(Ok(time)) if time <= now => Some(time),
+            Some(Ok(_)) => {
+                errors.push(FieldError::new(RECORDED_AT, "must not be in the future"));
+                None
+            }
+            Some(Err(reason)) => {
+                errors.push(FieldError::new(RECORDED_AT, reason));
+                None
+            }
+        };
+
+        let metric = match sport.parse_request(Value::Object(self.performance)) {
+            Ok(metric) => Some(metric),
+            Err(invalid) => {
+                errors.extend(invalid);
+                None
+            }
+        };
+
+        match (sportsman, recorded_at, metric) {
+            (Some(sportsman), Some(recorded_at), Some(metric)) if errors.is_empty() => {
+                Ok((sportsman, Record::new(metric, recorded_at)))
+            }
+            _ => Err(errors),
+        }
+    }
+}
+
+/// RFC 3339 time or a date taken as its midnight in UTC.
+/// Postgres keeps timestamps with microsecond precision
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc).trunc_subsecs(6));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(Default::default()).and_utc())
+        .map_err(|_| "must be an RFC 3339 time or a YYYY-MM-DD date".to_string())
+}
@@ -0,0 +1,251 @@
+use crate::models::audit::{AuditEntry, AuditFilter};
+use crate::models::error::Error;
+use crate::models::performance_tracker::{PerformanceTracker, Record, Sportsmen};
+use crate::models::responses::Page;
+use crate::models::sportsman::Sportsman;
+use crate::models::tenant::Tenant;
+use crate::service::registry::{Sport, SportRegistry};
+use crate::traits::traits::Pool;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+struct State {
+    last_id: i32,
+    tenants: HashMap<Tenant, Sportsmen>,
+    /// Entries are only appended
+    audit: Vec<(Tenant, AuditEntry)>,
+}
+
+impl State {
+    fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    /// Sportsmen of the tenant, created for the new tenant
+    fn sportsmen(&mut self, tenant: &Tenant) -> &mut Sportsmen {
+        self.tenants.entry(tenant.clone()).or_default()
+    }
+
+    /// Adds the record creating the sportsman if needed, returns sportsman's id
+    fn add(&mut self, tenant: &Tenant, sportsman: &Sportsman, record: &Record) -> i32 {
+        let stored = self.sportsmen(tenant).get_key_value(sportsman);
+        let id = match stored {
+            Some((stored, _)) => stored.id().expect("Stored sportsman always has an id"),
+            None => {
+                let id = self.next_id();
+                let created = Sportsman::unchecked_new(sportsman.name()).with_id(id);
+                self.sportsmen(tenant).insert(created, Vec::new());
+                id
+            }
+        };
+
+        let metrics = self
+            .sportsmen(tenant)
+            .get_mut(sportsman)
+            .expect("Sportsman was inserted above");
+        let ind = metrics.partition_point(|r| r.recorded_at <= record.recorded_at);
+        metrics.insert(ind, record.clone());
+
+        id
+    }
+
+    fn audit(&mut self, tenant: &Tenant, entry: &AuditEntry) {
+        self.audit.push((tenant.clone(), entry.clone()));
+    }
+}
+
+/// Storage that lives only while the service is running.
+/// Useful for running the service and its tests without a database
+#[derive(Debug, Default)]
+pub struct MemoryPool(Mutex<State>);
+
+impl MemoryPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Pool for MemoryPool {
+    /// Add new record creating the sportsman if needed, returns sportsman's id
+    async fn add_performance(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        _sport: &Sport,
+        record: &Record,
+        audit: &AuditEntry,
+    ) -> Result<i32, Error> {
+        let mut state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        let id = state.add(tenant, sportsman, record);
+        state.audit(tenant, audit);
+
+        Ok(id)
+    }
+
+    /// Records are added under one lock, so readers never see a part of them
+    async fn import_performances(
+        &self,
+        tenant: &Tenant,
+        _sport: &Sport,
+        records: &[(Sportsman, Record)],
+        audit: &[AuditEntry],
+    ) -> Result<Vec<i32>, Error> {
+        let mut state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        let ids = records
+            .iter()
+            .map(|(sportsman, record)| state.add(tenant, sportsman, record))
+            .collect();
+        for entry in audit {
+            state.audit(tenant, entry);
+        }
+
+        Ok(ids)
+    }
+
+    /// Removes the whole history of the sport
+    async fn remove_performance(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        sport: &Sport,
+        audit: &AuditEntry,
+    ) -> Result<(), Error> {
+        let mut state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        let metrics = state
+            .sportsmen(tenant)
+            .get_mut(sportsman)
+            .ok_or(Error::SportsmanNotFound)?;
+
+        let len = metrics.len();
+        metrics.retain(|r| r.metric.sport_name() != sport.name);
+
+        if metrics.len() == len {
+            return Err(Error::PerformanceNotFound);
+        }
+        state.audit(tenant, audit);
+
+        Ok(())
+    }
+
+    /// Records of all sports are kept together
+    async fn create_storage(&self, _sport: &Sport) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Return PerformanceTracker struct with the copy of stored data
+    async fn get_performance_tracker(
+        &self,
+        _registry: &SportRegistry,
+    ) -> Result<PerformanceTracker, Error> {
+        let state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        Ok(PerformanceTracker::new(state.tenants.clone()))
+    }
+
+    /// Creates sportsman with the profile and returns the id
+    async fn create_sportsman(&self, tenant: &Tenant, sportsman: &Sportsman) -> Result<i32, Error> {
+        let mut state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        if state.sportsmen(tenant).contains_key(sportsman) {
+            return Err(Error::SportsmanAlreadyExists);
+        }
+
+        let id = state.next_id();
+        state
+            .sportsmen(tenant)
+            .insert(sportsman.clone().with_id(id), Vec::new());
+
+        Ok(id)
+    }
+
+    /// Replaces name and profile of the sportsman keeping all their metrics
+    async fn update_sportsman(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        updated: &Sportsman,
+        audit: &[AuditEntry],
+    ) -> Result<(), Error> {
+        let mut state = self.0.lock().expect("Memory pool lock is poisoned");
+        let sportsmen = state.sportsmen(tenant);
+
+        let Some((stored, _)) = sportsmen.get_key_value(sportsman) else {
+            return Err(Error::SportsmanNotFound);
+        };
+        let id = stored.id().expect("Stored sportsman always has an id");
+
+        if sportsman != updated && sportsmen.contains_key(updated) {
+            return Err(Error::SportsmanAlreadyExists);
+        }
+
+        let metrics = sportsmen
+            .remove(sportsman)
+            .expect("Sportsman was found above");
+        sportsmen.insert(updated.clone().with_id(id), metrics);
+        for entry in audit {
+            state.audit(tenant, entry);
+        }
+
+        Ok(())
+    }
+
+    /// Removes sportsman with all their metrics
+    async fn remove_sportsman(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        audit: &[AuditEntry],
+    ) -> Result<(), Error> {
+        let mut state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        state
+            .sportsmen(tenant)
+            .remove(sportsman)
+            .ok_or(Error::SportsmanNotFound)?;
+        for entry in audit {
+            state.audit(tenant, entry);
+        }
+
+        Ok(())
+    }
+
+    /// Entries are kept in the order they were added
+    async fn get_audit(
+        &self,
+        tenant: &Tenant,
+        filter: &AuditFilter,
+    ) -> Result<Page<AuditEntry>, Error> {
+        let state = self.0.lock().expect("Memory pool lock is poisoned");
+
+        let entries: Vec<&AuditEntry> = state
+            .audit
+            .iter()
+            .filter(|(owner, entry)| owner == tenant && filter.matches(entry))
+            .map(|(_, entry)| entry)
+            .collect();
+
+        Ok(Page {
+            total: entries.len(),
+            offset: filter.offset,
+            limit: filter.limit,
+            items: entries
+                .into_iter()
+                .skip(filter.offset)
+                .take(filter.limit)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Memory is always available
+    async fn ping(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn close(&self) {}
+}
@@ -0,0 +1,49 @@
+use crate::models::error::Problem;
+use axum::extract::Request;
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Takes the request id from `x-request-id` header or generates it, returns it in the same header
+/// and completes problem details with it and the path, so the failed request can be found in logs
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid(value))
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:032x}", rand::random::<u128>()));
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let mut response = next.run(request).await;
+
+    if let Some(mut problem) = response.extensions_mut().remove::<Problem>() {
+        match &problem.cause {
+            Some(cause) => log::error!("[{id}] {method} {path}: {}: {cause}", problem.code),
+            None => log::info!("[{id}] {method} {path}: {}", problem.detail),
+        }
+
+        problem.instance = Some(path);
+        problem.request_id = Some(id.clone());
+        // headers of the original response are kept, e.g. `WWW-Authenticate`
+        let (_, body) = problem.render().into_parts();
+        *response.body_mut() = body;
+        response.headers_mut().remove(CONTENT_LENGTH);
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// Ids from clients are echoed back, so only short printable ones are accepted
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic())
+}
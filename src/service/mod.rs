@@ -1,3 +1,13 @@
+pub mod auth;
+pub mod config;
 pub mod core;
+pub mod extract;
+pub mod import;
+pub mod memory;
+pub mod middleware;
 pub mod models;
+pub mod openapi;
 pub mod postgres;
+pub mod registry;
+pub mod sql;
+pub mod sqlite;
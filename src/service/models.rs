@@ -1,50 +1,591 @@
+use crate::models::audit::{AuditEntry, AuditFilter, Operation};
+use crate::models::error::Error;
 use crate::models::metrics::biathlon::{Accuracy, Biathlon};
+use crate::models::metrics::cycling::{Cadence, Cycling, ElevationGain, Power};
 use crate::models::metrics::running::Running;
+use crate::models::metrics::swimming::{PoolLength, Stroke, Swimming, Time};
 use crate::models::metrics::weight_lifting::{LiftedWeight, Weight, WeightLifting};
-use crate::models::metrics::{biathlon, running};
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use crate::models::metrics::{biathlon, cycling, running, swimming};
+use crate::models::metrics::{serialize_f32, widen};
+use crate::models::responses::Page;
+use crate::models::sportsman::{Profile, Sex, Sportsman};
+use crate::models::units::UnitSystem;
+use crate::models::validation::{Checks, FieldError};
+use crate::traits::traits::Validate;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct RunningPerformance {
-    distance: f32,
-    speed: f32,
+    pub distance: f32,
+    pub speed: f32,
 }
 
-impl Into<Running> for RunningPerformance {
-    fn into(self) -> Running {
-        Running::new(running::Distance(self.distance), running::Speed(self.speed))
+impl From<RunningPerformance> for Running {
+    fn from(p: RunningPerformance) -> Self {
+        Running::new(running::Distance(p.distance), running::Speed(p.speed))
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+impl Validate for RunningPerformance {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Checks::new()
+            .positive("distance", self.distance)
+            .positive("speed", self.speed)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct BiathlonPerformance {
-    accuracy: f32,
-    distance: f32,
-    speed: f32,
+    pub accuracy: f32,
+    pub distance: f32,
+    pub speed: f32,
 }
 
-impl Into<Biathlon> for BiathlonPerformance {
-    fn into(self) -> Biathlon {
+impl From<BiathlonPerformance> for Biathlon {
+    fn from(p: BiathlonPerformance) -> Self {
         Biathlon::new(
-            Accuracy(self.accuracy),
-            biathlon::Distance(self.distance),
-            biathlon::Speed(self.speed),
+            Accuracy(p.accuracy),
+            biathlon::Distance(p.distance),
+            biathlon::Speed(p.speed),
         )
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+impl Validate for BiathlonPerformance {
+    /// Accuracy is the percentage of hit targets
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Checks::new()
+            .between("accuracy", self.accuracy, 0.0, 100.0)
+            .positive("distance", self.distance)
+            .positive("speed", self.speed)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct WeightLiftingPerformance {
-    weight: f32,
-    lifted_weight: f32,
+    pub weight: f32,
+    pub lifted_weight: f32,
+}
+
+impl From<WeightLiftingPerformance> for WeightLifting {
+    fn from(p: WeightLiftingPerformance) -> Self {
+        WeightLifting::new(Weight(p.weight), LiftedWeight(p.lifted_weight))
+    }
 }
 
-impl Into<WeightLifting> for WeightLiftingPerformance {
-    fn into(self) -> WeightLifting {
-        WeightLifting::new(Weight(self.weight), LiftedWeight(self.lifted_weight))
+impl Validate for WeightLiftingPerformance {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Checks::new()
+            .positive("weight", self.weight)
+            .positive("lifted_weight", self.lifted_weight)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct SwimmingPerformance {
+    pub distance: f32,
+    pub time: f32,
+    pub stroke: Stroke,
+    pub pool_length: f32,
+}
+
+impl From<SwimmingPerformance> for Swimming {
+    fn from(p: SwimmingPerformance) -> Self {
+        Swimming::new(
+            swimming::Distance(p.distance),
+            Time(p.time),
+            p.stroke,
+            PoolLength(p.pool_length),
+        )
+    }
+}
+
+impl Validate for SwimmingPerformance {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Checks::new()
+            .positive("distance", self.distance)
+            .positive("time", self.time)
+            .positive("pool_length", self.pool_length)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct CyclingPerformance {
+    pub distance: f32,
+    pub duration: f32,
+    pub average_power: f32,
+    pub average_cadence: f32,
+    pub elevation_gain: f32,
+}
+
+impl From<CyclingPerformance> for Cycling {
+    fn from(p: CyclingPerformance) -> Self {
+        Cycling::new(
+            cycling::Distance(p.distance),
+            cycling::Duration(p.duration),
+            Power(p.average_power),
+            Cadence(p.average_cadence),
+            ElevationGain(p.elevation_gain),
+        )
+    }
+}
+
+impl Validate for CyclingPerformance {
+    /// Power and cadence can be zero for a ride without a power meter or cadence sensor
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        Checks::new()
+            .positive("distance", self.distance)
+            .positive("duration", self.duration)
+            .non_negative("average_power", self.average_power)
+            .non_negative("average_cadence", self.average_cadence)
+            .non_negative("elevation_gain", self.elevation_gain)
+            .finish()
     }
 }
 
 #[derive(Eq, PartialEq, Hash, Debug)]
 pub struct Id(pub i32);
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NewSportsman {
+    pub name: String,
+    #[serde(flatten)]
+    pub profile: Profile,
+}
+
+impl TryFrom<NewSportsman> for Sportsman {
+    type Error = Error;
+
+    fn try_from(new: NewSportsman) -> Result<Self, Self::Error> {
+        Sportsman::new(new.name)?.with_profile(new.profile)
+    }
+}
+
+/// Absent fields are kept as is, `null` clears the field
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct SportsmanUpdate {
+    /// New name, all metrics are kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<NaiveDate>)]
+    pub birth_date: Option<Option<NaiveDate>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<Sex>)]
+    pub sex: Option<Option<Sex>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<f32>)]
+    pub body_mass: Option<Option<f32>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub nationality: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>)]
+    pub club: Option<Option<String>>,
+}
+
+impl SportsmanUpdate {
+    /// Returns validated sportsman with the update applied
+    pub fn apply(self, sportsman: Sportsman) -> Result<Sportsman, Error> {
+        let mut profile = sportsman.profile().clone();
+
+        if let Some(birth_date) = self.birth_date {
+            profile.birth_date = birth_date;
+        }
+        if let Some(sex) = self.sex {
+            profile.sex = sex;
+        }
+        if let Some(body_mass) = self.body_mass {
+            profile.body_mass = body_mass;
+        }
+        if let Some(nationality) = self.nationality {
+            profile.nationality = nationality;
+        }
+        if let Some(club) = self.club {
+            profile.club = club;
+        }
+
+        let mut updated = Sportsman::new(self.name.unwrap_or_else(|| sportsman.name()))?;
+        if let Some(id) = sportsman.id() {
+            updated = updated.with_id(id);
+        }
+        updated.with_profile(profile)
+    }
+}
+
+/// Distinguishes explicit `null` (`Some(None)`) from the absent field (`None`)
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    Name,
+    RecordedAt,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Number of skipped items
+    pub offset: Option<usize>,
+    /// Max number of returned items, 50 by default and 100 at most
+    pub limit: Option<usize>,
+    /// Field to sort by
+    pub sort: Option<SortField>,
+    /// Sort order
+    pub order: Option<Order>,
+    /// Only names starting with this prefix are returned
+    pub prefix: Option<String>,
+}
+
+impl ListQuery {
+    const DEFAULT_LIMIT: usize = 50;
+    const MAX_LIMIT: usize = 100;
+
+    pub fn offset(&self) -> usize {
+        self.offset.unwrap_or(0)
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT)
+    }
+
+    /// Filters items by name prefix, sorts them and cuts the requested page
+    pub fn apply(&self, mut items: Vec<ListItem>) -> Page {
+        if let Some(prefix) = &self.prefix {
+            items.retain(|item| item.name.starts_with(prefix.as_str()));
+        }
+
+        match self.sort.unwrap_or_default() {
+            SortField::Name => items.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortField::RecordedAt => items.sort_by(|a, b| {
+                a.recorded_at
+                    .cmp(&b.recorded_at)
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+        }
+
+        if let Order::Desc = self.order.unwrap_or_default() {
+            items.reverse();
+        }
+
+        let total = items.len();
+        let (offset, limit) = (self.offset(), self.limit());
+
+        Page {
+            total,
+            offset,
+            limit,
+            items: items
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|item| item.json)
+                .collect(),
+        }
+    }
+}
+
+/// Listed entity with the values it can be filtered and sorted by
+pub struct ListItem {
+    pub name: String,
+    pub recorded_at: Option<DateTime<Utc>>,
+    pub json: serde_json::Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LeaderboardQuery {
+    /// Field to rank by, e.g. `speed`
+    pub by: String,
+    /// `desc` (default) puts the biggest values first
+    pub order: Option<Order>,
+    /// Max number of entries, 10 by default and 100 at most
+    pub limit: Option<usize>,
+}
+
+impl LeaderboardQuery {
+    const DEFAULT_LIMIT: usize = 10;
+    const MAX_LIMIT: usize = 100;
+
+    pub fn descending(&self) -> bool {
+        !matches!(self.order, Some(Order::Asc))
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(Self::DEFAULT_LIMIT)
+            .min(Self::MAX_LIMIT)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditQuery {
+    /// Only changes of this sportsman
+    pub sportsman: Option<String>,
+    /// Only changes of this sport
+    pub sport: Option<String>,
+    /// Only changes made at or after this time, e.g. `2025-03-08T00:00:00Z`
+    pub since: Option<DateTime<Utc>>,
+    /// Number of skipped entries
+    pub offset: Option<usize>,
+    /// Max number of returned entries, 50 by default and 100 at most
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    const DEFAULT_LIMIT: usize = 50;
+    const MAX_LIMIT: usize = 100;
+
+    pub fn filter(&self) -> AuditFilter {
+        AuditFilter {
+            sportsman: self.sportsman.clone(),
+            sport: self.sport.clone(),
+            since: self.since,
+            offset: self.offset.unwrap_or(0),
+            limit: self
+                .limit
+                .unwrap_or(Self::DEFAULT_LIMIT)
+                .min(Self::MAX_LIMIT),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UnitsQuery {
+    /// Units of the measured values, `metric` by default.
+    /// Can also be set by `units` parameter of `Accept` header, e.g. `application/json; units=imperial`
+    pub units: Option<UnitSystem>,
+}
+
+/// Result of a successful change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Message {
+    pub message: String,
+}
+
+impl Message {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Liveness {
+    /// Always `ok`
+    pub status: String,
+    pub version: String,
+    pub uptime_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Readiness {
+    /// `ready` or `not_ready`
+    pub status: String,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub checks: ReadinessChecks,
+    /// Number of known sportsmen
+    pub sportsmen: usize,
+}
+
+/// `ok` or the reason the dependency isn't ready
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessChecks {
+    pub database: String,
+    /// `loading` until the stored performances are in the cache
+    pub tracker: String,
+}
+
+/// Sportsman as returned by the service, age is computed at the time of the request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SportsmanProfile {
+    pub id: Option<i32>,
+    pub name: String,
+    pub birth_date: Option<NaiveDate>,
+    /// Full years, known if birth date is
+    pub age: Option<u32>,
+    pub sex: Option<Sex>,
+    /// Body mass in kg
+    #[serde(serialize_with = "serialize_mass")]
+    pub body_mass: Option<f32>,
+    /// ISO 3166-1 alpha-3 country code, e.g. `NOR`
+    pub nationality: Option<String>,
+    pub club: Option<String>,
+}
+
+impl From<&Sportsman> for SportsmanProfile {
+    fn from(sportsman: &Sportsman) -> Self {
+        let profile = sportsman.profile();
+        Self {
+            id: sportsman.id(),
+            name: sportsman.name(),
+            birth_date: profile.birth_date,
+            age: sportsman.age(Utc::now().date_naive()),
+            sex: profile.sex,
+            body_mass: profile.body_mass,
+            nationality: profile.nationality.clone(),
+            club: profile.club.clone(),
+        }
+    }
+}
+
+/// Item of the sportsmen list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SportsmanOverview {
+    #[serde(flatten)]
+    pub profile: SportsmanProfile,
+    /// Sports the sportsman has records of
+    pub sports: Vec<String>,
+    #[serde(serialize_with = "serialize_optional_time")]
+    pub last_recorded_at: Option<DateTime<Utc>>,
+}
+
+/// Performance with the time it was recorded at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Recorded<T> {
+    #[serde(flatten)]
+    pub performance: T,
+    #[serde(serialize_with = "serialize_time")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Latest performance of the sportsman
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LatestPerformance<T> {
+    pub name: String,
+    pub performance: Recorded<T>,
+}
+
+/// Sportsmen ranked by their best value of the field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Ranking {
+    pub sport: String,
+    /// Field sportsmen are ranked by
+    pub by: String,
+    pub order: Order,
+    /// Unit of the values, absent for unitless fields
+    pub unit: Option<String>,
+    pub entries: Vec<RankingEntry>,
+}
+
+/// Best value of the sportsman, equal values share the rank
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RankingEntry {
+    pub rank: usize,
+    pub name: String,
+    #[serde(serialize_with = "serialize_f32")]
+    pub value: f32,
+    #[serde(serialize_with = "serialize_time")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Change of the performance data, values are stored records in metric units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuditRecord {
+    /// Name of the API key or subject of the token that made the change
+    pub actor: String,
+    pub operation: Operation,
+    pub sport: String,
+    pub sportsman: String,
+    /// Latest record replaced by the added one, the removed history or the previous name
+    pub before: Option<serde_json::Value>,
+    /// Added record or the new name
+    pub after: Option<serde_json::Value>,
+    #[serde(serialize_with = "serialize_time")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl From<AuditEntry> for AuditRecord {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            actor: entry.actor,
+            operation: entry.operation,
+            sport: entry.sport,
+            sportsman: entry.sportsman,
+            before: entry.before,
+            after: entry.after,
+            recorded_at: entry.recorded_at,
+        }
+    }
+}
+
+/// Result of the CSV import, either all valid rows are stored or none
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ImportReport {
+    /// Number of stored rows
+    pub imported: usize,
+    pub rejected: Vec<RejectedRow>,
+}
+
+/// Row that wasn't imported and errors of all its invalid fields
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RejectedRow {
+    /// Line of the row in the file, the header is line 1
+    pub line: u64,
+    pub name: String,
+    pub errors: Vec<FieldError>,
+}
+
+/// Times are shown with `+00:00` offset as in the stored records
+fn serialize_time<S: Serializer>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&time.to_rfc3339())
+}
+
+fn serialize_optional_time<S: Serializer>(
+    time: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    time.map(|time| time.to_rfc3339()).serialize(serializer)
+}
+
+fn serialize_mass<S: Serializer>(mass: &Option<f32>, serializer: S) -> Result<S::Ok, S::Error> {
+    mass.map(widen).serialize(serializer)
+}
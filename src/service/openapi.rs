@@ -0,0 +1,201 @@
+use crate::models::error::{Error, Problem};
+use crate::models::units::{Unit, UnitSystem};
+use crate::service::auth::API_KEY_HEADER;
+use crate::service::registry::SportRegistry;
+use utoipa::openapi::path::{Operation, ParameterIn};
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+};
+use utoipa::openapi::{
+    ContentBuilder, ObjectBuilder, OneOfBuilder, OpenApi, Ref, RefOr, ResponseBuilder, Schema, Type,
+};
+use utoipa::{PartialSchema, ToSchema};
+
+/// Record of any sport, the sports' schemas are listed by [`document_sports`].
+/// Its schema is only known at runtime, so the types containing it refer to it by name
+pub enum Performance {}
+
+impl PartialSchema for Performance {
+    fn schema() -> RefOr<Schema> {
+        Ref::from_schema_name(Self::name()).into()
+    }
+}
+
+impl ToSchema for Performance {}
+
+/// Request of any sport, the sports' schemas are listed by [`document_sports`]
+pub enum PerformanceRequest {}
+
+impl PartialSchema for PerformanceRequest {
+    fn schema() -> RefOr<Schema> {
+        Ref::from_schema_name(Self::name()).into()
+    }
+}
+
+impl ToSchema for PerformanceRequest {}
+
+/// Lists schemas of the registered sports in [`Performance`] and [`PerformanceRequest`],
+/// names of the sports become the values of `sport` path parameter.
+/// Measured fields of the requests also accept a value with unit
+pub fn document_sports(doc: &mut OpenApi, registry: &SportRegistry) {
+    let components = doc.components.get_or_insert_with(Default::default);
+
+    let mut performance = OneOfBuilder::new();
+    let mut request = OneOfBuilder::new();
+    for sport in registry.iter() {
+        let mut request_schema = sport.doc.request.clone();
+        if let RefOr::T(Schema::Object(object)) = &mut request_schema {
+            for (field, units) in &sport.units {
+                if let Some(property) = object.properties.remove(*field) {
+                    object
+                        .properties
+                        .insert(field.to_string(), with_unit(property, units.stored));
+                }
+            }
+        }
+
+        components.schemas.extend(sport.doc.nested.iter().cloned());
+        components
+            .schemas
+            .insert(sport.doc.response_name.clone(), sport.doc.response.clone());
+        components
+            .schemas
+            .insert(sport.request_name.to_string(), request_schema);
+
+        performance = performance.item(Ref::from_schema_name(&sport.doc.response_name));
+        request = request.item(Ref::from_schema_name(sport.request_name));
+    }
+    components
+        .schemas
+        .insert(Performance::name().to_string(), performance.into());
+    components
+        .schemas
+        .insert(PerformanceRequest::name().to_string(), request.into());
+
+    let sports: Vec<&str> = registry.iter().map(|sport| sport.name).collect();
+    for path in doc.paths.paths.values_mut() {
+        let operations = [
+            &mut path.get,
+            &mut path.post,
+            &mut path.put,
+            &mut path.patch,
+            &mut path.delete,
+        ];
+        for operation in operations.into_iter().flatten() {
+            list_sports(operation, &sports);
+        }
+    }
+}
+
+/// Routes other than health checks take an API key or a bearer token,
+/// both schemes are listed and every such operation answers 401 and 403
+pub fn document_security(doc: &mut OpenApi) {
+    let components = doc.components.get_or_insert_with(Default::default);
+    components.add_security_scheme(
+        "api_key",
+        SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+    );
+    components.add_security_scheme(
+        "bearer",
+        SecurityScheme::Http(
+            HttpBuilder::new()
+                .scheme(HttpAuthScheme::Bearer)
+                .bearer_format("JWT")
+                .build(),
+        ),
+    );
+
+    let unauthenticated = problem_response(
+        "Нет или неверные учетные данные / Missing or invalid credentials",
+        Error::Unauthenticated("Missing X-API-Key header or bearer token".to_string()),
+        "/sportsmen",
+    );
+    let forbidden = problem_response(
+        "Роль не позволяет запрос / Role doesn't allow the request",
+        Error::Forbidden("athlete ann can't read data of Bob".to_string()),
+        "/sportsmen/Bob",
+    );
+
+    let paths = doc.paths.paths.iter_mut();
+    for (_, path) in paths.filter(|(path, _)| !path.starts_with("/health")) {
+        let operations = [
+            &mut path.get,
+            &mut path.post,
+            &mut path.put,
+            &mut path.patch,
+            &mut path.delete,
+        ];
+        for operation in operations.into_iter().flatten() {
+            operation.security = Some(vec![
+                SecurityRequirement::new("api_key", Vec::<String>::new()),
+                SecurityRequirement::new("bearer", Vec::<String>::new()),
+            ]);
+            let responses = &mut operation.responses.responses;
+            responses.insert("401".to_string(), unauthenticated.clone());
+            responses.insert("403".to_string(), forbidden.clone());
+        }
+    }
+}
+
+fn problem_response(
+    description: &str,
+    error: Error,
+    instance: &str,
+) -> RefOr<utoipa::openapi::Response> {
+    let mut problem = error.problem();
+    problem.instance = Some(instance.to_string());
+    problem.request_id = Some("3f2b8c1d9e0a4b6c8d7e6f5a4b3c2d1e".to_string());
+    let example = serde_json::to_value(problem).expect("Problem is plain json");
+
+    ResponseBuilder::new()
+        .description(description)
+        .content(
+            "application/problem+json",
+            ContentBuilder::new()
+                .schema(Some(Ref::from_schema_name(Problem::name())))
+                .example(Some(example))
+                .build(),
+        )
+        .build()
+        .into()
+}
+
+/// Plain number is taken in the stored unit
+fn with_unit(number: RefOr<Schema>, stored: Unit) -> RefOr<Schema> {
+    let symbols: Vec<&str> = Unit::of_dimension(stored.dimension())
+        .map(Unit::symbol)
+        .collect();
+
+    OneOfBuilder::new()
+        .description(Some(format!(
+            "Number in {stored} or value with unit, e.g. {{\"value\": 3.1, \"unit\": \"{}\"}}",
+            stored.counterpart(UnitSystem::Imperial)
+        )))
+        .item(number)
+        .item(
+            ObjectBuilder::new()
+                .property("value", ObjectBuilder::new().schema_type(Type::Number))
+                .required("value")
+                .property(
+                    "unit",
+                    ObjectBuilder::new()
+                        .schema_type(Type::String)
+                        .enum_values(Some(symbols)),
+                )
+                .required("unit"),
+        )
+        .into()
+}
+
+fn list_sports(operation: &mut Operation, sports: &[&str]) {
+    let parameters = operation.parameters.iter_mut().flatten();
+    for parameter in parameters.filter(|p| p.name == "sport" && p.parameter_in == ParameterIn::Path)
+    {
+        parameter.schema = Some(
+            ObjectBuilder::new()
+                .schema_type(Type::String)
+                .enum_values(Some(sports.to_vec()))
+                .into(),
+        );
+    }
+}
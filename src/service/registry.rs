@@ -0,0 +1,521 @@
+use crate::models::error::Error;
+use crate::models::metrics::biathlon::Biathlon;
+use crate::models::metrics::custom::{CustomMetric, FieldType, SportSchema, SportsSchema};
+use crate::models::metrics::cycling::Cycling;
+use crate::models::metrics::running::Running;
+use crate::models::metrics::swimming::Swimming;
+use crate::models::metrics::weight_lifting::WeightLifting;
+use crate::models::units::{self, Unit, UnitSystem, Units};
+use crate::models::validation::FieldError;
+use crate::service::config::StartupError;
+use crate::service::models::{
+    BiathlonPerformance, CyclingPerformance, RunningPerformance, SwimmingPerformance,
+    WeightLiftingPerformance,
+};
+use crate::traits::traits::{Metric, Pool, Validate};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+use utoipa::openapi::{Object, ObjectBuilder, RefOr, Schema, Type};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Real,
+    Text,
+}
+
+/// Column of the sport's table, named as the field of the request model
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+}
+
+impl Column {
+    pub fn real(name: &'static str) -> Self {
+        Self {
+            name,
+            column_type: ColumnType::Real,
+        }
+    }
+
+    pub fn text(name: &'static str) -> Self {
+        Self {
+            name,
+            column_type: ColumnType::Text,
+        }
+    }
+}
+
+/// OpenAPI schemas of the sport's request and of its records
+pub struct SportDoc {
+    pub request: RefOr<Schema>,
+    /// Name the record schema is registered under, e.g. `Running`
+    pub response_name: String,
+    pub response: RefOr<Schema>,
+    /// Schemas the request and the record refer to, e.g. `Stroke`
+    pub nested: Vec<(String, RefOr<Schema>)>,
+}
+
+type ParseFn = Box<dyn Fn(Value) -> Result<Box<dyn Metric>, serde_json::Error> + Send + Sync>;
+type RequestFn = Box<dyn Fn(Value) -> Result<Box<dyn Metric>, Vec<FieldError>> + Send + Sync>;
+type ColumnsFn = Box<dyn Fn(&dyn Metric) -> Option<Map<String, Value>> + Send + Sync>;
+
+/// Everything needed to route, parse and store records of one sport
+pub struct Sport {
+    /// Name used in routes, e.g. `running`
+    pub name: &'static str,
+    pub table: &'static str,
+    pub columns: Vec<Column>,
+    /// Name of the request model reported in format errors
+    pub request_name: &'static str,
+    /// Numeric fields sportsmen can be ranked by
+    pub ranking_fields: Vec<&'static str>,
+    /// Units of the measured fields, values are stored in [`Units::stored`]
+    pub units: Vec<(&'static str, Units)>,
+    /// Schema of the sport defined at runtime, its table isn't created by migrations
+    pub schema: Option<SportSchema>,
+    /// Schemas the sport is described by in the OpenAPI document
+    pub doc: SportDoc,
+    parse: ParseFn,
+    parse_request: RequestFn,
+    to_columns: ColumnsFn,
+}
+
+impl Sport {
+    /// Sport of the compiled-in metric `T` with the request model `P`.
+    /// Fields of the serialized metric are the table columns
+    pub fn typed<T, P>(
+        name: &'static str,
+        table: &'static str,
+        request_name: &'static str,
+        columns: Vec<Column>,
+    ) -> Self
+    where
+        T: Metric + Serialize + DeserializeOwned + ToSchema + From<P>,
+        P: DeserializeOwned + Serialize + Default + Validate + ToSchema + 'static,
+    {
+        let request_columns = columns.clone();
+
+        let mut nested = Vec::new();
+        P::schemas(&mut nested);
+        T::schemas(&mut nested);
+        // derived fields are added to the served json only
+        let mut response = T::schema();
+        if let RefOr::T(Schema::Object(object)) = &mut response {
+            for (field, description) in T::derived_field_docs() {
+                object.properties.insert(
+                    field.to_string(),
+                    ObjectBuilder::new()
+                        .schema_type(Type::Number)
+                        .description(Some(*description))
+                        .into(),
+                );
+            }
+        }
+
+        Self {
+            name,
+            table,
+            columns,
+            request_name,
+            ranking_fields: T::ranking_fields().to_vec(),
+            units: T::field_units().to_vec(),
+            schema: None,
+            doc: SportDoc {
+                request: P::schema(),
+                response_name: T::name().to_string(),
+                response,
+                nested,
+            },
+            parse: Box::new(|value| Ok(Box::new(serde_json::from_value::<T>(value)?))),
+            // fields that were read are validated even if others are invalid,
+            // so all errors are reported at once
+            parse_request: Box::new(move |value| {
+                let (performance, mut errors) = deserialize_request::<P>(value, &request_columns);
+                let Some(performance) = performance else {
+                    return Err(errors);
+                };
+                if let Err(invalid) = performance.validate() {
+                    let reported: Vec<String> =
+                        errors.iter().map(|e| root_field(&e.field)).collect();
+                    errors.extend(
+                        invalid
+                            .into_iter()
+                            .filter(|e| !reported.contains(&root_field(&e.field))),
+                    );
+                }
+                if errors.is_empty() {
+                    Ok(Box::new(T::from(performance)))
+                } else {
+                    Err(errors)
+                }
+            }),
+            to_columns: Box::new(|metric| {
+                let metric = metric.as_any().downcast_ref::<T>()?;
+                match serde_json::to_value(metric) {
+                    Ok(Value::Object(map)) => Some(map),
+                    _ => None,
+                }
+            }),
+        }
+    }
+
+    /// Sport defined by the schema, stored in `sport_<name>` table.
+    /// Names are leaked as the schema is loaded once on startup
+    pub fn custom(schema: SportSchema) -> Self {
+        let name: &'static str = schema.name.clone().leak();
+        let response_name: &'static str = schema.title().to_string().leak();
+        let columns = schema
+            .fields
+            .iter()
+            .map(|field| {
+                let name: &'static str = field.name.clone().leak();
+                if field.field_type.is_numeric() {
+                    Column::real(name)
+                } else {
+                    Column::text(name)
+                }
+            })
+            .collect::<Vec<_>>();
+        let ranking_fields = schema
+            .fields
+            .iter()
+            .zip(&columns)
+            .filter(|(field, _)| field.field_type.is_numeric())
+            .map(|(_, column)| column.name)
+            .collect();
+        // unit given in the schema is used for conversions if it is a known one
+        let units = schema
+            .fields
+            .iter()
+            .zip(&columns)
+            .filter(|(field, _)| field.field_type == FieldType::Number)
+            .filter_map(|(field, column)| {
+                let unit: Unit = field.unit.as_deref()?.parse().ok()?;
+                Some((column.name, Units::of(unit)))
+            })
+            .collect();
+
+        let parse_schema = schema.clone();
+        let request_schema = schema.clone();
+        let object: RefOr<Schema> = RefOr::T(Schema::Object(schema_object(&schema)));
+
+        Self {
+            name,
+            table: format!("sport_{name}").leak(),
+            columns,
+            request_name: format!("{response_name}Performance").leak(),
+            ranking_fields,
+            units,
+            schema: Some(schema),
+            doc: SportDoc {
+                request: object.clone(),
+                response_name: response_name.to_string(),
+                response: object,
+                nested: Vec::new(),
+            },
+            parse: Box::new(move |value| match parse_schema.read(value) {
+                (fields, errors) if errors.is_empty() => {
+                    Ok(Box::new(CustomMetric::new(name, response_name, fields)))
+                }
+                (_, errors) => Err(serde_json::Error::custom(
+                    errors
+                        .iter()
+                        .map(FieldError::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )),
+            }),
+            // fields that were read are validated even if others are invalid,
+            // so all errors are reported at once
+            parse_request: Box::new(move |value| {
+                let (fields, mut errors) = request_schema.read(value);
+                if let Err(invalid) = request_schema.validate(&fields) {
+                    errors.extend(invalid);
+                }
+                if errors.is_empty() {
+                    Ok(Box::new(CustomMetric::new(name, response_name, fields)))
+                } else {
+                    Err(errors)
+                }
+            }),
+            to_columns: Box::new(move |metric| {
+                let metric = metric.as_any().downcast_ref::<CustomMetric>()?;
+                (metric.sport_name() == name).then(|| metric.fields().clone())
+            }),
+        }
+    }
+
+    /// Builds the metric from the stored row converted to json
+    pub fn parse(&self, value: Value) -> Result<Box<dyn Metric>, serde_json::Error> {
+        (self.parse)(value)
+    }
+
+    /// Builds the metric from the request body checking the rules of the sport,
+    /// returns errors of all invalid fields. Measured fields may be sent with a unit,
+    /// e.g. `{"value": 3.1, "unit": "mi"}`, and are converted to the stored one
+    pub fn parse_request(&self, mut value: Value) -> Result<Box<dyn Metric>, Vec<FieldError>> {
+        let mut errors = match value.as_object_mut() {
+            Some(object) => units::read_units(&self.units, object),
+            None => Vec::new(),
+        };
+
+        match (self.parse_request)(value) {
+            Ok(metric) if errors.is_empty() => Ok(metric),
+            Ok(_) => Err(errors),
+            Err(invalid) => {
+                // fields with invalid units would be reported once more as mistyped
+                let reported: Vec<String> = errors.iter().map(|e| root_field(&e.field)).collect();
+                errors.extend(
+                    invalid
+                        .into_iter()
+                        .filter(|e| !reported.contains(&root_field(&e.field))),
+                );
+                Err(errors)
+            }
+        }
+    }
+
+    /// Converts stored values of the serialized metric to the units of the system
+    pub fn show_units(&self, value: &mut Value, system: UnitSystem) {
+        if let Some(object) = value.as_object_mut() {
+            units::show_units(&self.units, object, system);
+        }
+    }
+
+    pub fn units_of(&self, field: &str) -> Option<Units> {
+        self.units
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, units)| *units)
+    }
+
+    /// Values of the columns in their order, None if the metric belongs to another sport
+    pub fn column_values(&self, metric: &dyn Metric) -> Option<Vec<Value>> {
+        let mut map = (self.to_columns)(metric)?;
+        Some(
+            self.columns
+                .iter()
+                .map(|c| map.remove(c.name).unwrap_or(Value::Null))
+                .collect(),
+        )
+    }
+}
+
+/// Records of the sport defined at runtime have the same fields as its request
+fn schema_object(schema: &SportSchema) -> Object {
+    let mut object = ObjectBuilder::new().description(schema.description.clone());
+    for field in &schema.fields {
+        let description = match (&field.description, &field.unit) {
+            (Some(description), Some(unit)) => Some(format!("{description}, {unit}")),
+            (Some(description), None) => Some(description.clone()),
+            (None, Some(unit)) => Some(unit.clone()),
+            (None, None) => None,
+        };
+        let schema_type = match field.field_type {
+            FieldType::Number => Type::Number,
+            FieldType::Integer => Type::Integer,
+            FieldType::Text => Type::String,
+        };
+        let mut property = ObjectBuilder::new()
+            .schema_type(schema_type)
+            .description(description)
+            .minimum(field.min)
+            .maximum(field.max);
+        if !field.values.is_empty() {
+            property = property.enum_values(Some(field.values.clone()));
+        }
+        object = object.property(&field.name, property).required(&field.name);
+    }
+
+    object.build()
+}
+
+fn root_field(path: &str) -> String {
+    path.split('.').next().unwrap_or(path).to_string()
+}
+
+/// Reports all missing, mistyped and unknown columns at once, anything else serde rejects
+/// (e.g. unknown enum value) is reported for the field it was found in.
+/// Invalid fields are filled from `P::default()`, so the request is returned for validation
+/// of the fields that were read, errors of the filled fields are already reported
+fn deserialize_request<P: DeserializeOwned + Serialize + Default>(
+    value: Value,
+    columns: &[Column],
+) -> (Option<P>, Vec<FieldError>) {
+    let Value::Object(mut object) = value else {
+        return (None, vec![FieldError::new("body", "must be an object")]);
+    };
+    let Ok(Value::Object(defaults)) = serde_json::to_value(P::default()) else {
+        unreachable!("request models are serialized as objects");
+    };
+
+    let mut errors = Vec::new();
+    for column in columns {
+        let error = match (object.get(column.name), column.column_type) {
+            (None | Some(Value::Null), _) => FieldError::new(column.name, "is required"),
+            (Some(Value::Number(_)), ColumnType::Real)
+            | (Some(Value::String(_)), ColumnType::Text) => continue,
+            (Some(_), ColumnType::Real) => FieldError::new(column.name, "must be a number"),
+            (Some(_), ColumnType::Text) => FieldError::new(column.name, "must be a string"),
+        };
+        errors.push(error);
+        object.remove(column.name);
+    }
+    object.retain(|field, _| {
+        let known = columns.iter().any(|column| column.name == field);
+        if !known {
+            errors.push(FieldError::new(field, "is unknown"));
+        }
+        known
+    });
+    for (field, default) in &defaults {
+        object
+            .entry(field.clone())
+            .or_insert_with(|| default.clone());
+    }
+
+    // each rejected field is replaced once, so the loop ends
+    loop {
+        match serde_path_to_error::deserialize(Value::Object(object.clone())) {
+            Ok(performance) => return (Some(performance), errors),
+            Err(e) => {
+                let path = e.path().to_string();
+                errors.push(FieldError::new(&path, e.inner().to_string()));
+                let field = root_field(&path);
+                match defaults.get(&field) {
+                    Some(default) if object.get(&field) != Some(default) => {
+                        object.insert(field, default.clone());
+                    }
+                    _ => return (None, errors),
+                }
+            }
+        }
+    }
+}
+
+/// Sports known to the service, storages and handlers are driven by it
+#[derive(Default)]
+pub struct SportRegistry {
+    sports: Vec<Sport>,
+}
+
+impl SportRegistry {
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+
+        registry.register(Sport::typed::<Running, RunningPerformance>(
+            "running",
+            "Running",
+            "RunningPerformance",
+            vec![Column::real("distance"), Column::real("speed")],
+        ));
+        registry.register(Sport::typed::<Biathlon, BiathlonPerformance>(
+            "biathlon",
+            "Biathlon",
+            "BiathlonPerformance",
+            vec![
+                Column::real("accuracy"),
+                Column::real("distance"),
+                Column::real("speed"),
+            ],
+        ));
+        registry.register(Sport::typed::<WeightLifting, WeightLiftingPerformance>(
+            "weight_lifting",
+            "WeightLifting",
+            "WeightLiftingPerformance",
+            vec![Column::real("weight"), Column::real("lifted_weight")],
+        ));
+        registry.register(Sport::typed::<Swimming, SwimmingPerformance>(
+            "swimming",
+            "Swimming",
+            "SwimmingPerformance",
+            vec![
+                Column::real("distance"),
+                Column::real("time"),
+                Column::text("stroke"),
+                Column::real("pool_length"),
+            ],
+        ));
+        registry.register(Sport::typed::<Cycling, CyclingPerformance>(
+            "cycling",
+            "Cycling",
+            "CyclingPerformance",
+            vec![
+                Column::real("distance"),
+                Column::real("duration"),
+                Column::real("average_power"),
+                Column::real("average_cadence"),
+                Column::real("elevation_gain"),
+            ],
+        ));
+
+        registry
+    }
+
+    /// Built-in sports and the ones listed in the schema file (`.toml` or `.json`)
+    pub fn load(schema_path: Option<&Path>) -> Result<Self, StartupError> {
+        let mut registry = Self::builtin();
+        let Some(path) = schema_path else {
+            return Ok(registry);
+        };
+
+        let invalid = |reason: String| StartupError::InvalidSportsSchema {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let content = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let schema: SportsSchema = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| invalid(e.to_string()))?,
+            Some("json") => serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?,
+            _ => return Err(invalid("expected .toml or .json file".to_string())),
+        };
+
+        for sport in schema.sports {
+            sport.check().map_err(invalid)?;
+            if registry.get(&sport.name).is_some() {
+                return Err(invalid(format!("sport {} is already defined", sport.name)));
+            }
+            log::info!("Sport {} is defined by {}", sport.name, path.display());
+            registry.register(Sport::custom(sport));
+        }
+
+        Ok(registry)
+    }
+
+    /// Creates tables of the sports defined at runtime, built-in ones are created by migrations
+    pub async fn create_storages<P: Pool>(&self, pool: &P) -> Result<(), StartupError> {
+        for sport in self.sports.iter().filter(|s| s.schema.is_some()) {
+            pool.create_storage(sport)
+                .await
+                .map_err(StartupError::CreateStorage)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sport with the same name is replaced
+    pub fn register(&mut self, sport: Sport) {
+        self.sports.retain(|s| s.name != sport.name);
+        self.sports.push(sport);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Sport> {
+        self.sports.iter().find(|s| s.name == name)
+    }
+
+    /// Like [`get`](Self::get), but unknown sport is reported as [`Error::SportNotFound`]
+    pub fn find(&self, name: &str) -> Result<&Sport, Error> {
+        self.get(name)
+            .ok_or_else(|| Error::SportNotFound(name.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Sport> {
+        self.sports.iter()
+    }
+}
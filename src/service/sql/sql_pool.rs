@@ -0,0 +1,601 @@
+use crate::models::audit::{AuditEntry, AuditFilter};
+use crate::models::error::Error;
+use crate::models::performance_tracker::{Metrics, PerformanceTracker, Record, Sportsmen};
+use crate::models::responses::Page;
+use crate::models::sportsman::{Profile, Sportsman};
+use crate::models::tenant::Tenant;
+use crate::service::models::Id;
+use crate::service::registry::{ColumnType, Sport, SportRegistry};
+use crate::traits::traits::Pool;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_json::{json, Map, Value};
+use sqlx::{ColumnIndex, Database, Decode, Encode, Executor, IntoArguments, Row, Type};
+use std::collections::HashMap;
+
+/// SQL that differs between the databases, the rest of the queries is shared by the pools
+pub trait Dialect: Database {
+    /// Definition of the auto-incremented primary key of the tables of sports
+    const ID_COLUMN: &'static str;
+    /// Definition of `recorded_at` column defaulting to the current time
+    const RECORDED_AT_COLUMN: &'static str;
+
+    /// Casts the parameter or the column, e.g. Postgres needs the type of nullable parameters
+    fn cast(expression: &str, sql_type: &str) -> String;
+
+    fn rows_affected(result: &Self::QueryResult) -> u64;
+}
+
+/// Pool of the SQL database, backends differ only in connecting, migrations and [`Dialect`]
+pub struct SqlPool<DB: Database>(pub sqlx::Pool<DB>);
+
+// types of the bound parameters and the read columns have to be supported by the backend
+impl<DB> SqlPool<DB>
+where
+    DB: Dialect,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    usize: ColumnIndex<DB::Row>,
+    for<'q> i32: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> i64: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> f32: Decode<'q, DB> + Type<DB>,
+    for<'q> String: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> NaiveDate: Decode<'q, DB> + Type<DB>,
+    for<'q> DateTime<Utc>: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> &'q str: Encode<'q, DB> + Type<DB>,
+    for<'q> Option<&'q str>: Encode<'q, DB>,
+    for<'q> Option<String>: Encode<'q, DB>,
+    for<'q> Option<f32>: Encode<'q, DB>,
+    for<'q> Option<NaiveDate>: Encode<'q, DB>,
+    for<'q> Option<DateTime<Utc>>: Encode<'q, DB>,
+{
+    fn get_sportsmen_table_name(&self) -> &'static str {
+        "Sportsmen"
+    }
+
+    /// Sportsmen of all tenants
+    async fn get_all_sportsmen(&self) -> Result<Vec<(Id, Tenant, Sportsman)>, sqlx::Error> {
+        let req = format!(
+            "SELECT id, tenant, name, birth_date, sex, body_mass, nationality, club FROM {}",
+            self.get_sportsmen_table_name()
+        );
+
+        type Row = (
+            i32,
+            String,
+            String,
+            Option<NaiveDate>,
+            Option<String>,
+            Option<f32>,
+            Option<String>,
+            Option<String>,
+        );
+
+        let res = sqlx::query_as::<_, Row>(req.as_str())
+            .fetch_all(&self.0)
+            .await?
+            .into_iter()
+            .map(
+                |(id, tenant, name, birth_date, sex, body_mass, nationality, club)| {
+                    let profile = Profile {
+                        birth_date,
+                        sex: sex.and_then(|s| s.parse().ok()),
+                        body_mass,
+                        nationality,
+                        club,
+                    };
+                    (
+                        Id(id),
+                        Tenant::unchecked_new(tenant),
+                        Sportsman::unchecked_new(name)
+                            .with_id(id)
+                            .unchecked_with_profile(profile),
+                    )
+                },
+            )
+            .collect();
+
+        Ok(res)
+    }
+
+    /// Inserts sportsman if sportsman with the same name doesn't exist in the tenant,
+    /// returns sportsman's id. Safe for concurrent calls, conflicting insert just returns
+    /// the existing row
+    async fn upsert_sportsman(
+        &self,
+        conn: &mut DB::Connection,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+    ) -> Result<i32, sqlx::Error> {
+        let req = format!(
+            "INSERT INTO {} (tenant, name) VALUES ($1, $2) \
+            ON CONFLICT (tenant, name) DO UPDATE SET name = EXCLUDED.name RETURNING id",
+            self.get_sportsmen_table_name()
+        );
+
+        let row = sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(sportsman.name())
+            .fetch_one(conn)
+            .await?;
+
+        row.try_get(0)
+    }
+
+    async fn get_sportsman_id(
+        &self,
+        conn: &mut DB::Connection,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let req = format!(
+            "SELECT id FROM {} WHERE tenant=$1 AND name=$2",
+            self.get_sportsmen_table_name()
+        );
+
+        let row = sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(sportsman.name())
+            .fetch_optional(conn)
+            .await?;
+
+        row.map(|row| row.try_get(0)).transpose()
+    }
+
+    async fn add_metric(
+        &self,
+        conn: &mut DB::Connection,
+        sportsman_id: i32,
+        sport: &Sport,
+        record: &Record,
+    ) -> Result<(), sqlx::Error> {
+        let values = sport.column_values(record.metric.as_ref()).ok_or_else(|| {
+            sqlx::Error::TypeNotFound {
+                type_name: record.metric.response_name().to_string(),
+            }
+        })?;
+
+        let columns: Vec<&str> = sport.columns.iter().map(|c| c.name).collect();
+        let placeholders: Vec<String> = (0..columns.len()).map(|i| format!("${}", i + 3)).collect();
+        let req = format!(
+            "INSERT INTO {} (sportsman_id, recorded_at, {}) VALUES ($1, $2, {})",
+            sport.table,
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+
+        let mut query_builder = sqlx::query(req.as_str())
+            .bind(sportsman_id)
+            .bind(record.recorded_at);
+
+        for (column, value) in sport.columns.iter().zip(values) {
+            query_builder = match column.column_type {
+                ColumnType::Real => query_builder.bind(value.as_f64().map(|v| v as f32)),
+                ColumnType::Text => query_builder.bind(value.as_str().map(str::to_string)),
+            };
+        }
+
+        query_builder.execute(conn).await?;
+
+        Ok(())
+    }
+
+    /// Returns all records of the sport ordered by `recorded_at`,
+    /// rows are parsed by the sport without the rules checked for requests
+    async fn get_all_metrics(&self, sport: &Sport) -> Result<Vec<(Id, Record)>, sqlx::Error> {
+        let columns: Vec<&str> = sport.columns.iter().map(|c| c.name).collect();
+        let req = format!(
+            "SELECT sportsman_id, recorded_at, {} FROM {} ORDER BY recorded_at",
+            columns.join(", "),
+            sport.table
+        );
+
+        sqlx::query(req.as_str())
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| {
+                let mut fields = Map::new();
+                for (ind, column) in sport.columns.iter().enumerate() {
+                    let value = match column.column_type {
+                        ColumnType::Real => json!(row.try_get::<f32, _>(ind + 2)?),
+                        ColumnType::Text => json!(row.try_get::<String, _>(ind + 2)?),
+                    };
+                    fields.insert(column.name.to_string(), value);
+                }
+
+                let metric = sport
+                    .parse(Value::Object(fields))
+                    .map_err(|e| sqlx::Error::Decode(e.into()))?;
+                let recorded_at: DateTime<Utc> = row.try_get(1)?;
+
+                Ok((Id(row.try_get(0)?), Record::new(metric, recorded_at)))
+            })
+            .collect()
+    }
+
+    /// Appends the entry to the audit log, `Audit` table rejects updates and deletes
+    async fn add_audit(
+        &self,
+        conn: &mut DB::Connection,
+        tenant: &Tenant,
+        entry: &AuditEntry,
+    ) -> Result<(), sqlx::Error> {
+        let req = format!(
+            "INSERT INTO Audit \
+            (tenant, actor, operation, sport, sportsman, before, after, recorded_at) \
+            VALUES ($1, $2, $3, $4, $5, {}, {}, $8)",
+            DB::cast("$6", "JSONB"),
+            DB::cast("$7", "JSONB")
+        );
+
+        sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(&entry.actor)
+            .bind(entry.operation.as_str())
+            .bind(&entry.sport)
+            .bind(&entry.sportsman)
+            .bind(entry.before.as_ref().map(Value::to_string))
+            .bind(entry.after.as_ref().map(Value::to_string))
+            .bind(entry.recorded_at)
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes the whole history of the sport
+    async fn remove_metric_if_exists(
+        &self,
+        conn: &mut DB::Connection,
+        sportsman_id: i32,
+        sport: &Sport,
+    ) -> Result<bool, sqlx::Error> {
+        let req = format!("DELETE FROM {} WHERE sportsman_id=$1", sport.table);
+
+        let res = sqlx::query(req.as_str())
+            .bind(sportsman_id)
+            .execute(conn)
+            .await?;
+
+        Ok(DB::rows_affected(&res) > 0)
+    }
+}
+
+impl<DB> Pool for SqlPool<DB>
+where
+    DB: Dialect,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    usize: ColumnIndex<DB::Row>,
+    for<'q> i32: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> i64: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> f32: Decode<'q, DB> + Type<DB>,
+    for<'q> String: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> NaiveDate: Decode<'q, DB> + Type<DB>,
+    for<'q> DateTime<Utc>: Encode<'q, DB> + Decode<'q, DB> + Type<DB>,
+    for<'q> &'q str: Encode<'q, DB> + Type<DB>,
+    for<'q> Option<&'q str>: Encode<'q, DB>,
+    for<'q> Option<String>: Encode<'q, DB>,
+    for<'q> Option<f32>: Encode<'q, DB>,
+    for<'q> Option<NaiveDate>: Encode<'q, DB>,
+    for<'q> Option<DateTime<Utc>>: Encode<'q, DB>,
+{
+    /// Add new record creating the sportsman if needed, previous records of the same type are kept.
+    /// Everything is done in one transaction, returns sportsman's id
+    async fn add_performance(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        sport: &Sport,
+        record: &Record,
+        audit: &AuditEntry,
+    ) -> Result<i32, Error> {
+        let mut tx = self.0.begin().await?;
+
+        let sportsman_id = self.upsert_sportsman(&mut tx, tenant, sportsman).await?;
+        self.add_metric(&mut tx, sportsman_id, sport, record)
+            .await?;
+        self.add_audit(&mut tx, tenant, audit).await?;
+
+        tx.commit().await?;
+
+        Ok(sportsman_id)
+    }
+
+    /// Sportsmen are upserted one by one, so concurrent imports of the same names don't conflict
+    async fn import_performances(
+        &self,
+        tenant: &Tenant,
+        sport: &Sport,
+        records: &[(Sportsman, Record)],
+        audit: &[AuditEntry],
+    ) -> Result<Vec<i32>, Error> {
+        let mut tx = self.0.begin().await?;
+
+        let mut ids = Vec::with_capacity(records.len());
+        for (sportsman, record) in records {
+            let sportsman_id = self.upsert_sportsman(&mut tx, tenant, sportsman).await?;
+            self.add_metric(&mut tx, sportsman_id, sport, record)
+                .await?;
+            ids.push(sportsman_id);
+        }
+        for entry in audit {
+            self.add_audit(&mut tx, tenant, entry).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(ids)
+    }
+
+    /// Removes metric if it exists
+    async fn remove_performance(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        sport: &Sport,
+        audit: &AuditEntry,
+    ) -> Result<(), Error> {
+        let mut tx = self.0.begin().await?;
+
+        let id = self
+            .get_sportsman_id(&mut tx, tenant, sportsman)
+            .await?
+            .ok_or(Error::SportsmanNotFound)?;
+        if !self.remove_metric_if_exists(&mut tx, id, sport).await? {
+            return Err(Error::PerformanceNotFound);
+        }
+        self.add_audit(&mut tx, tenant, audit).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Table has the same layout as the ones of built-in sports, columns aren't altered
+    /// if the table exists, so changing fields of the sport needs manual migration
+    async fn create_storage(&self, sport: &Sport) -> Result<(), Error> {
+        let columns: Vec<String> = sport
+            .columns
+            .iter()
+            .map(|c| match c.column_type {
+                ColumnType::Real => format!("{} REAL NOT NULL", c.name),
+                ColumnType::Text => format!("{} TEXT NOT NULL", c.name),
+            })
+            .collect();
+        let req = format!(
+            "CREATE TABLE IF NOT EXISTS {table} \
+            ({id}, {columns}, sportsman_id INT NOT NULL, recorded_at {recorded_at}, \
+            FOREIGN KEY (sportsman_id) REFERENCES {sportsmen}(ID) ON DELETE CASCADE)",
+            table = sport.table,
+            id = DB::ID_COLUMN,
+            recorded_at = DB::RECORDED_AT_COLUMN,
+            columns = columns.join(", "),
+            sportsmen = self.get_sportsmen_table_name(),
+        );
+        let index = format!(
+            "CREATE INDEX IF NOT EXISTS {table}_sportsman_recorded_at \
+            ON {table} (sportsman_id, recorded_at)",
+            table = sport.table
+        );
+
+        let mut tx = self.0.begin().await?;
+        sqlx::query(req.as_str()).execute(&mut *tx).await?;
+        sqlx::query(index.as_str()).execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        log::info!("Storage of {} is ready", sport.name);
+
+        Ok(())
+    }
+
+    /// Return PerformanceTracker struct loaded from the Database
+    async fn get_performance_tracker(
+        &self,
+        registry: &SportRegistry,
+    ) -> Result<PerformanceTracker, Error> {
+        let sportsmen = self.get_all_sportsmen().await?;
+
+        let mut tenants: HashMap<Tenant, Sportsmen> = HashMap::new();
+
+        let mut metrics_map: HashMap<Id, Metrics> = HashMap::new();
+
+        for sport in registry.iter() {
+            for (id, record) in self.get_all_metrics(sport).await? {
+                metrics_map.entry(id).or_default().push(record);
+            }
+        }
+
+        for (id, tenant, sportsman) in sportsmen {
+            let mut metrics = metrics_map.remove(&id).unwrap_or_default();
+            metrics.sort_by_key(|r| r.recorded_at);
+            tenants
+                .entry(tenant)
+                .or_default()
+                .insert(sportsman, metrics);
+        }
+
+        Ok(PerformanceTracker::new(tenants))
+    }
+
+    /// Creates sportsman with the profile and returns the id
+    async fn create_sportsman(&self, tenant: &Tenant, sportsman: &Sportsman) -> Result<i32, Error> {
+        let req = format!(
+            "INSERT INTO {} (tenant, name, birth_date, sex, body_mass, nationality, club) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (tenant, name) DO NOTHING RETURNING id",
+            self.get_sportsmen_table_name()
+        );
+
+        let profile = sportsman.profile();
+        let row = sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(sportsman.name())
+            .bind(profile.birth_date)
+            .bind(profile.sex.map(|s| s.as_str()))
+            .bind(profile.body_mass)
+            .bind(profile.nationality.as_deref())
+            .bind(profile.club.as_deref())
+            .fetch_optional(&self.0)
+            .await?
+            .ok_or(Error::SportsmanAlreadyExists)?;
+
+        Ok(row.try_get(0)?)
+    }
+
+    /// Replaces name and profile of the sportsman keeping all their metrics
+    async fn update_sportsman(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        updated: &Sportsman,
+        audit: &[AuditEntry],
+    ) -> Result<(), Error> {
+        let req = format!(
+            "UPDATE {} SET name=$3, birth_date=$4, sex=$5, body_mass=$6, nationality=$7, club=$8 \
+            WHERE tenant=$1 AND name=$2",
+            self.get_sportsmen_table_name()
+        );
+
+        let mut tx = self.0.begin().await?;
+
+        let profile = updated.profile();
+        let res = sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(sportsman.name())
+            .bind(updated.name())
+            .bind(profile.birth_date)
+            .bind(profile.sex.map(|s| s.as_str()))
+            .bind(profile.body_mass)
+            .bind(profile.nationality.as_deref())
+            .bind(profile.club.as_deref())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(e) if e.is_unique_violation() => {
+                    Error::SportsmanAlreadyExists
+                }
+                e => e.into(),
+            })?;
+        if DB::rows_affected(&res) == 0 {
+            return Err(Error::SportsmanNotFound);
+        }
+        for entry in audit {
+            self.add_audit(&mut tx, tenant, entry).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Removes sportsman, their metrics are removed by cascade
+    async fn remove_sportsman(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        audit: &[AuditEntry],
+    ) -> Result<(), Error> {
+        let req = format!(
+            "DELETE FROM {} WHERE tenant=$1 AND name=$2",
+            self.get_sportsmen_table_name()
+        );
+
+        let mut tx = self.0.begin().await?;
+
+        let res = sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(sportsman.name())
+            .execute(&mut *tx)
+            .await?;
+        if DB::rows_affected(&res) == 0 {
+            return Err(Error::SportsmanNotFound);
+        }
+        for entry in audit {
+            self.add_audit(&mut tx, tenant, entry).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Entries are ordered by id, which is the order they were committed in
+    async fn get_audit(
+        &self,
+        tenant: &Tenant,
+        filter: &AuditFilter,
+    ) -> Result<Page<AuditEntry>, Error> {
+        let condition = format!(
+            "WHERE tenant=$1 AND ({} IS NULL OR sportsman=$2) AND ({} IS NULL OR sport=$3) \
+            AND ({} IS NULL OR recorded_at >= $4)",
+            DB::cast("$2", "TEXT"),
+            DB::cast("$3", "TEXT"),
+            DB::cast("$4", "TIMESTAMPTZ")
+        );
+        let count = format!("SELECT COUNT(*) FROM Audit {condition}");
+        let req = format!(
+            "SELECT actor, operation, sport, sportsman, {}, {}, recorded_at \
+            FROM Audit {condition} ORDER BY id LIMIT $5 OFFSET $6",
+            DB::cast("before", "TEXT"),
+            DB::cast("after", "TEXT")
+        );
+
+        let total: i64 = sqlx::query_scalar(count.as_str())
+            .bind(tenant.as_str())
+            .bind(filter.sportsman.as_deref())
+            .bind(filter.sport.as_deref())
+            .bind(filter.since)
+            .fetch_one(&self.0)
+            .await?;
+
+        let items = sqlx::query(req.as_str())
+            .bind(tenant.as_str())
+            .bind(filter.sportsman.as_deref())
+            .bind(filter.sport.as_deref())
+            .bind(filter.since)
+            .bind(filter.limit as i64)
+            .bind(filter.offset as i64)
+            .fetch_all(&self.0)
+            .await?
+            .iter()
+            .map(|row| {
+                let json = |ind: usize| -> Result<Option<Value>, sqlx::Error> {
+                    row.try_get::<Option<String>, _>(ind)?
+                        .map(|text| serde_json::from_str(&text))
+                        .transpose()
+                        .map_err(|e| sqlx::Error::Decode(e.into()))
+                };
+                let operation: String = row.try_get(1)?;
+
+                Ok(AuditEntry {
+                    actor: row.try_get(0)?,
+                    operation: operation
+                        .parse()
+                        .map_err(|e: String| sqlx::Error::Decode(e.into()))?,
+                    sport: row.try_get(2)?,
+                    sportsman: row.try_get(3)?,
+                    before: json(4)?,
+                    after: json(5)?,
+                    recorded_at: row.try_get(6)?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(Page {
+            total: total as usize,
+            offset: filter.offset,
+            limit: filter.limit,
+            items,
+        })
+    }
+
+    async fn ping(&self) -> Result<(), Error> {
+        sqlx::query("SELECT 1").execute(&self.0).await?;
+
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.0.close().await;
+    }
+}
@@ -0,0 +1,68 @@
+use crate::service::config::{sqlite_connect_options, PoolConfig, StartupError};
+use crate::service::sql::sql_pool::{Dialect, SqlPool};
+use sqlx::migrate::MigrateError;
+use sqlx::sqlite::{SqlitePoolOptions, SqliteQueryResult};
+use sqlx::Sqlite;
+
+/// SQLite storage, lets the service run locally without Postgres
+pub type SqliteDBPool = SqlPool<Sqlite>;
+
+impl SqliteDBPool {
+    pub async fn new(config: &PoolConfig) -> Result<Self, StartupError> {
+        let options = sqlite_connect_options()?
+            .create_if_missing(true)
+            .foreign_keys(true);
+
+        // single connection serializes writes and keeps `sqlite::memory:` database shared,
+        // reads are served from the PerformanceTracker anyway
+        let connection = config
+            .retry
+            .retry("Opening SQLite database", || {
+                SqlitePoolOptions::new()
+                    .max_connections(1)
+                    .acquire_timeout(config.connect_timeout)
+                    .connect_with(options.clone())
+            })
+            .await
+            .map_err(StartupError::Connection)?;
+
+        let pool = Self(connection);
+        pool.migrate().await.map_err(StartupError::Migration)?;
+
+        Ok(pool)
+    }
+
+    /// Applies migrations embedded from `migrations/sqlite` that weren't applied yet.
+    /// Foreign keys are turned off meanwhile, so tables can be rebuilt without cascading
+    /// deletes, the pragma can't be changed inside the transaction of a migration
+    pub async fn migrate(&self) -> Result<(), MigrateError> {
+        let migrator = sqlx::migrate!("./migrations/sqlite");
+
+        let mut conn = self.0.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await?;
+        let res = migrator.run(&mut *conn).await;
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await?;
+        res?;
+        log::info!("Database schema is up to date");
+
+        Ok(())
+    }
+}
+
+/// Values are stored in SQLite with the declared types as affinities, so casts aren't needed
+impl Dialect for Sqlite {
+    const ID_COLUMN: &'static str = "ID INTEGER PRIMARY KEY AUTOINCREMENT";
+    const RECORDED_AT_COLUMN: &'static str = "TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP";
+
+    fn cast(expression: &str, _sql_type: &str) -> String {
+        expression.to_string()
+    }
+
+    fn rows_affected(result: &SqliteQueryResult) -> u64 {
+        result.rows_affected()
+    }
+}
@@ -1,32 +1,202 @@
+use crate::models::audit::{AuditEntry, AuditFilter};
 use crate::models::error::Error;
-use crate::models::performance_tracker::PerformanceTracker;
-use crate::models::sportsman::Sportsman;
+use crate::models::metrics::widen;
+use crate::models::performance_tracker::{PerformanceTracker, Record, SportsmanSummary};
+use crate::models::responses::Page;
+use crate::models::sportsman::{Profile, Sportsman};
+use crate::models::tenant::Tenant;
+use crate::models::units::Units;
+use crate::models::validation::FieldError;
+use crate::service::registry::{Sport, SportRegistry};
 use axum::response::IntoResponse;
 use std::any::Any;
 use std::fmt::Debug;
+use std::future::Future;
 
 pub trait Metric: Any + Debug + IntoResponse + Sync + Send {
     fn as_any(&self) -> &dyn Any;
     fn clone_box(&self) -> Box<dyn Metric>;
     fn response_name(&self) -> &'static str;
+    /// Name of the sport used in routes
+    fn sport_name(&self) -> &'static str;
+    /// Numeric fields sportsmen can be ranked by
+    fn ranking_fields() -> &'static [&'static str]
+    where
+        Self: Sized;
+    /// Units of the measured fields including the derived ones, other fields are unitless
+    fn field_units() -> &'static [(&'static str, Units)]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+    /// Serialized metric without the derived fields
+    fn to_json(&self) -> serde_json::Value;
+    /// Numeric field of the serialized metric
+    fn field(&self, name: &str) -> Option<f32> {
+        self.to_json().get(name)?.as_f64().map(|v| v as f32)
+    }
+    /// Values computed from the metric and sportsman's profile, e.g. power per kg of body mass
+    fn derived_fields(&self, _profile: &Profile) -> Vec<(&'static str, f32)> {
+        Vec::new()
+    }
+    /// Names and descriptions of the fields [`Metric::derived_fields`] may add, shown in the API docs
+    fn derived_field_docs() -> &'static [(&'static str, &'static str)]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// Looks up both own and derived fields
+    fn field_for(&self, name: &str, profile: &Profile) -> Option<f32> {
+        self.field(name).or_else(|| {
+            self.derived_fields(profile)
+                .into_iter()
+                .find(|(field, _)| *field == name)
+                .map(|(_, value)| value)
+        })
+    }
+
+    fn to_json_for(&self, profile: &Profile) -> serde_json::Value {
+        let mut json = self.to_json();
+        if let Some(object) = json.as_object_mut() {
+            for (field, value) in self.derived_fields(profile) {
+                object.insert(field.to_string(), serde_json::json!(widen(value)));
+            }
+        }
+        json
+    }
+}
+
+/// Newtype of a measured value kept in the stored unit of [`Units`]
+pub trait Quantity {
+    const UNITS: Units;
+}
+
+/// Rules of the request model checked before the record is accepted
+pub trait Validate {
+    /// Returns errors of all invalid fields
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
 }
 
 pub trait SportPerformance {
-    async fn add_performance(&self, sportsman: Sportsman, metric: Box<dyn Metric>);
-    async fn get_performance<T: Metric + Clone>(&self, sportsman: &Sportsman) -> Result<T, Error>;
-    async fn remove_performance<T: Metric>(&self, sportsman: Sportsman) -> Result<(), Error>;
+    fn add_performance(
+        &self,
+        sportsman: Sportsman,
+        record: Record,
+    ) -> impl Future<Output = ()> + Send;
+    fn get_performance(
+        &self,
+        sportsman: &Sportsman,
+        sport: &str,
+    ) -> impl Future<Output = Result<Record, Error>> + Send;
+    fn get_history(
+        &self,
+        sportsman: &Sportsman,
+        sport: &str,
+    ) -> impl Future<Output = Result<Vec<Record>, Error>> + Send;
+    fn get_sportsmen(&self) -> impl Future<Output = Vec<SportsmanSummary>> + Send;
+    fn get_latest_performances(
+        &self,
+        sport: &str,
+    ) -> impl Future<Output = Vec<(Sportsman, Record)>> + Send;
+    fn get_histories(
+        &self,
+        sport: &str,
+    ) -> impl Future<Output = Vec<(Sportsman, Vec<Record>)>> + Send;
+    fn remove_performance(
+        &self,
+        sportsman: Sportsman,
+        sport: &str,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+    fn add_sportsman(&self, sportsman: Sportsman)
+        -> impl Future<Output = Result<(), Error>> + Send;
+    fn get_sportsman(
+        &self,
+        sportsman: &Sportsman,
+    ) -> impl Future<Output = Result<Sportsman, Error>> + Send;
+    fn update_sportsman(
+        &self,
+        sportsman: &Sportsman,
+        updated: Sportsman,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+    fn remove_sportsman(
+        &self,
+        sportsman: &Sportsman,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
 }
 
-pub trait Pool {
-    async fn add_performance(
+/// Storage backend of the service. Sportsmen are looked up within the tenant,
+/// their performances belong to the same tenant. Changes of performances are stored
+/// together with their [`AuditEntry`], so the audit log has every committed change.
+/// Futures are `Send`, so the service can be generic over the backend
+pub trait Pool: Send + Sync + 'static {
+    /// Creates the sportsman if needed, returns sportsman's id
+    fn add_performance(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        sport: &Sport,
+        record: &Record,
+        audit: &AuditEntry,
+    ) -> impl Future<Output = Result<i32, Error>> + Send;
+    /// Adds the records in one transaction creating the sportsmen if needed, nothing is stored
+    /// if any of them fails. `audit` has an entry per record.
+    /// Returns ids of the sportsmen in the order of the records
+    fn import_performances(
         &self,
+        tenant: &Tenant,
+        sport: &Sport,
+        records: &[(Sportsman, Record)],
+        audit: &[AuditEntry],
+    ) -> impl Future<Output = Result<Vec<i32>, Error>> + Send;
+    /// Removes the whole history of the sport, returns [`Error::SportsmanNotFound`]
+    /// or [`Error::PerformanceNotFound`] if there is nothing to remove
+    fn remove_performance(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        sport: &Sport,
+        audit: &AuditEntry,
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+    /// Creates the table of the sport defined at runtime if it doesn't exist
+    fn create_storage(&self, sport: &Sport) -> impl Future<Output = Result<(), Error>> + Send;
+    /// Loads records of all registered sports of all tenants
+    fn get_performance_tracker(
+        &self,
+        registry: &SportRegistry,
+    ) -> impl Future<Output = Result<PerformanceTracker, Error>> + Send;
+    /// Returns id of the created sportsman or [`Error::SportsmanAlreadyExists`]
+    fn create_sportsman(
+        &self,
+        tenant: &Tenant,
         sportsman: &Sportsman,
-        metric: Box<dyn Metric>,
-    ) -> Result<(), sqlx::error::Error>;
-    async fn remove_performance<T: Metric>(
+    ) -> impl Future<Output = Result<i32, Error>> + Send;
+    /// Returns [`Error::SportsmanAlreadyExists`] if the new name is taken.
+    /// `audit` has an entry per sport the sportsman has records of if they are renamed
+    fn update_sportsman(
         &self,
+        tenant: &Tenant,
         sportsman: &Sportsman,
-    ) -> Result<bool, sqlx::error::Error>;
-    async fn get_performance_tracker(&self) -> Result<PerformanceTracker, sqlx::Error>;
-    async fn add_sportsman(&self, sportsman: &Sportsman) -> Result<(), sqlx::Error>;
+        updated: &Sportsman,
+        audit: &[AuditEntry],
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+    /// `audit` has an entry per sport the sportsman has records of
+    fn remove_sportsman(
+        &self,
+        tenant: &Tenant,
+        sportsman: &Sportsman,
+        audit: &[AuditEntry],
+    ) -> impl Future<Output = Result<(), Error>> + Send;
+    /// Page of the audit log of the tenant
+    fn get_audit(
+        &self,
+        tenant: &Tenant,
+        filter: &AuditFilter,
+    ) -> impl Future<Output = Result<Page<AuditEntry>, Error>> + Send;
+    /// Checks that the storage can serve queries
+    fn ping(&self) -> impl Future<Output = Result<(), Error>> + Send;
+    /// Waits for checked out connections to be returned and closes them
+    fn close(&self) -> impl Future<Output = ()> + Send;
 }